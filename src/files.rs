@@ -0,0 +1,97 @@
+// src/files.rs
+// Utilitários para encontrar arquivos de áudio suportados no disco.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::playlist_io::cue;
+
+// Extensões aceitas pelo diálogo "Add" e ao arrastar arquivos para a janela
+pub const SUPPORTED_EXTENSIONS: [&str; 4] = ["mp3", "wav", "ogg", "flac"];
+// Extensões do diálogo "Add": áudio e CUE sheets (expandidos em faixas)
+pub const ADD_DIALOG_EXTENSIONS: [&str; 5] = ["mp3", "wav", "ogg", "flac", "cue"];
+
+pub fn is_supported_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SUPPORTED_EXTENSIONS.iter().any(|supported| supported.eq_ignore_ascii_case(ext)))
+}
+
+pub fn is_cue_sheet(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("cue"))
+}
+
+// Expande a lista de caminhos: arquivos suportados entram direto e pastas são
+// percorridas recursivamente (em ordem alfabética). CUE sheets viram uma entrada por faixa.
+// O resto é ignorado.
+pub fn collect_audio_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    for path in paths {
+        collect_into(path, &mut files, &mut visited);
+    }
+    files
+}
+
+// `visited` guarda as pastas já percorridas (canônicas): um link simbólico para uma pasta
+// acima (`dir/link -> ..`) não vira recursão infinita e a mesma pasta não entra duas vezes
+fn collect_into(path: &Path, files: &mut Vec<PathBuf>, visited: &mut HashSet<PathBuf>) {
+    if path.is_dir() {
+        match fs::canonicalize(path) {
+            Ok(canonical) => if !visited.insert(canonical) { return; },
+            Err(e) => { eprintln!("Failed to resolve directory {:?}: {}", path, e); return; }
+        }
+        let mut children: Vec<PathBuf> = match fs::read_dir(path) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok().map(|e| e.path())).collect(),
+            Err(e) => { eprintln!("Failed to read directory {:?}: {}", path, e); return; }
+        };
+        children.sort();
+        // Um arquivo de áudio descrito por um CUE da mesma pasta entra só pelas faixas do CUE
+        let covered_by_cue: HashSet<PathBuf> = children.iter()
+            .filter(|child| is_cue_sheet(child))
+            .filter_map(|child| cue::read(child).ok())
+            .flat_map(|sheet| sheet.tracks.into_iter().map(|track| track.file))
+            .collect();
+        for child in children.iter().filter(|child| !covered_by_cue.contains(*child)) {
+            collect_into(child, files, visited);
+        }
+    } else if is_cue_sheet(path) {
+        match cue::read(path) {
+            Ok(sheet) => files.extend(sheet.tracks.iter().map(|track| cue::track_path(path, track.number))),
+            Err(e) => eprintln!("Failed to read CUE sheet {:?}: {}", path, e),
+        }
+    } else if is_supported_audio(path) {
+        files.push(path.to_path_buf());
+    }
+}
+
+// Abre o gerenciador de arquivos do sistema mostrando os arquivos.
+// No Linux abre cada pasta (distinta) que contém os arquivos.
+// Faixas de CUE sheet mostram o próprio arquivo .cue.
+pub fn reveal_in_file_manager(paths: &[PathBuf]) -> io::Result<()> {
+    let targets: Vec<PathBuf> = paths.iter()
+        .map(|path| cue::split_track_path(path).map_or_else(|| path.clone(), |(cue_path, _)| cue_path.to_path_buf()))
+        .collect();
+    if cfg!(target_os = "windows") {
+        for path in &targets {
+            Command::new("explorer").arg(format!("/select,{}", path.display())).spawn()?;
+        }
+    } else if cfg!(target_os = "macos") {
+        Command::new("open").arg("-R").args(&targets).spawn()?;
+    } else {
+        let folders: BTreeSet<&Path> = targets.iter().filter_map(|p| p.parent()).collect();
+        for folder in folders {
+            Command::new("xdg-open").arg(folder).spawn()?;
+        }
+    }
+    Ok(())
+}
+
+// Abre uma pasta no gerenciador de arquivos
+pub fn open_folder(dir: &Path) -> io::Result<()> {
+    let program = if cfg!(target_os = "windows") { "explorer" } else if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    Command::new(program).arg(dir).spawn().map(|_| ())
+}
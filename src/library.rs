@@ -0,0 +1,237 @@
+// src/library.rs
+// Banco de dados próprio do player: histórico de reprodução e estatísticas por faixa.
+// Fica em um JSON na pasta de dados do app, separado do estado da janela e das playlists,
+// para o histórico poder crescer sem pesar no salvamento do eframe.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+pub const APP_NAME: &str = "Rust Music Player Lite";
+const LIBRARY_FILE: &str = "library.json";
+
+// --- Uma audição registrada ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenRecord {
+    pub path: PathBuf,
+    pub started_at: i64, // Unix timestamp (segundos)
+    pub played: Duration, // Tempo realmente tocado (sem pausas)
+    pub completed: bool, // false = pulada (Next, Stop, outra faixa escolhida)
+}
+
+impl ListenRecord {
+    pub fn started_at_local(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_opt(self.started_at, 0).single()
+    }
+}
+
+// --- Marcador com nome numa posição da faixa ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub position: Duration,
+}
+
+// --- Estatísticas de uma faixa ---
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackStats {
+    pub plays: u32,
+    pub skips: u32,
+    pub last_played: Option<i64>,
+    pub rating: u8, // 0 = sem avaliação, 1–5 estrelas
+    pub loved: bool,
+    // Onde a reprodução parou (só arquivos longos); some quando a faixa toca até o fim
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume_position: Option<Duration>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<Bookmark>, // Em ordem de posição
+}
+
+impl TrackStats {
+    // Peso no shuffle "Weighted": nota alta e "loved" puxam para frente, pulos empurram para o fim
+    pub fn shuffle_weight(&self) -> f64 {
+        let rating = if self.rating == 0 { 1.0 } else { self.rating as f64 / 3.0 };
+        let loved = if self.loved { 2.0 } else { 1.0 };
+        rating * loved / (1.0 + self.skips as f64)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Library {
+    pub history: Vec<ListenRecord>,
+    pub stats: HashMap<PathBuf, TrackStats>,
+    #[serde(skip)]
+    dirty: bool,
+    // Aumenta a cada mudança; as playlists inteligentes se recalculam quando ele muda
+    #[serde(skip)]
+    revision: u64,
+}
+
+impl Library {
+    fn file_path() -> Option<PathBuf> {
+        eframe::storage_dir(APP_NAME).map(|dir| dir.join(LIBRARY_FILE))
+    }
+
+    // Carrega o banco; se não existir (ou estiver corrompido) começa vazio
+    pub fn load() -> Self {
+        let Some(path) = Library::file_path() else { return Library::default(); };
+        match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                eprintln!("Library: Failed to parse {:?}: {}", path, e);
+                Library::default()
+            }),
+            Err(_) => Library::default(),
+        }
+    }
+
+    // Grava só se algo mudou. Escreve em um arquivo temporário e renomeia,
+    // para um fechamento no meio da escrita não corromper o banco.
+    pub fn save(&mut self) -> Result<(), String> {
+        if !self.dirty { return Ok(()); }
+        let path = Library::file_path().ok_or("No data directory available")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        }
+        let text = serde_json::to_string(self).map_err(|e| format!("Failed to serialize library: {}", e))?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, text).map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+        fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn touch(&mut self) {
+        self.dirty = true;
+        self.revision += 1;
+    }
+
+    pub fn stats(&self, path: &Path) -> Option<&TrackStats> {
+        self.stats.get(path)
+    }
+
+    pub fn rating(&self, path: &Path) -> u8 {
+        self.stats.get(path).map_or(0, |stats| stats.rating)
+    }
+
+    pub fn is_loved(&self, path: &Path) -> bool {
+        self.stats.get(path).is_some_and(|stats| stats.loved)
+    }
+
+    pub fn set_rating(&mut self, path: &Path, rating: u8) {
+        self.stats.entry(path.to_path_buf()).or_default().rating = rating.min(5);
+        self.touch();
+    }
+
+    pub fn set_loved(&mut self, path: &Path, loved: bool) {
+        self.stats.entry(path.to_path_buf()).or_default().loved = loved;
+        self.touch();
+    }
+
+    pub fn record_listen(&mut self, record: ListenRecord) {
+        let stats = self.stats.entry(record.path.clone()).or_default();
+        if record.completed { stats.plays += 1; } else { stats.skips += 1; }
+        stats.last_played = Some(record.started_at);
+        self.history.push(record);
+        self.touch();
+    }
+
+    pub fn resume_position(&self, path: &Path) -> Option<Duration> {
+        self.stats.get(path).and_then(|stats| stats.resume_position)
+    }
+
+    // Posições e marcadores não entram nas playlists inteligentes: só marcam o banco para salvar
+    pub fn set_resume_position(&mut self, path: &Path, position: Option<Duration>) {
+        if self.resume_position(path) == position { return; }
+        self.stats.entry(path.to_path_buf()).or_default().resume_position = position;
+        self.dirty = true;
+    }
+
+    pub fn bookmarks(&self, path: &Path) -> &[Bookmark] {
+        self.stats.get(path).map_or(&[], |stats| &stats.bookmarks)
+    }
+
+    pub fn add_bookmark(&mut self, path: &Path, bookmark: Bookmark) {
+        let bookmarks = &mut self.stats.entry(path.to_path_buf()).or_default().bookmarks;
+        let index = bookmarks.partition_point(|existing| existing.position <= bookmark.position);
+        bookmarks.insert(index, bookmark);
+        self.dirty = true;
+    }
+
+    pub fn remove_bookmark(&mut self, path: &Path, index: usize) {
+        let Some(stats) = self.stats.get_mut(path) else { return; };
+        if index < stats.bookmarks.len() {
+            stats.bookmarks.remove(index);
+            self.dirty = true;
+        }
+    }
+
+    // Arquivo movido/renomeado (organizador): estatísticas e histórico seguem o novo caminho
+    pub fn rename_path(&mut self, from: &Path, to: &Path) {
+        if let Some(stats) = self.stats.remove(from) { self.stats.insert(to.to_path_buf(), stats); }
+        for record in self.history.iter_mut().filter(|record| record.path == from) { record.path = to.to_path_buf(); }
+        self.touch();
+    }
+
+    // Histórico em CSV (uma linha por audição). `describe` devolve (artista, título, álbum).
+    // Recebe uma cópia do histórico para poder rodar fora da thread da UI.
+    pub fn history_csv(history: &[ListenRecord], mut describe: impl FnMut(&Path) -> (String, String, String)) -> String {
+        let mut out = String::from("started_at,artist,title,album,path,played_seconds,result\n");
+        for record in history {
+            let (artist, title, album) = describe(&record.path);
+            let started_at = record.started_at_local().map(|t| t.to_rfc3339()).unwrap_or_default();
+            let fields = [
+                started_at,
+                artist,
+                title,
+                album,
+                record.path.display().to_string(),
+                format!("{:.1}", record.played.as_secs_f64()),
+                if record.completed { "completed" } else { "skipped" }.to_string(),
+            ];
+            out.push_str(&fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// Aspas só quando necessário (RFC 4180)
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// --- Audição em andamento ---
+// O tempo tocado vem do relógio de reprodução (PlaybackClock) quando a audição termina.
+pub struct ListenSession {
+    pub path: PathBuf,
+    started_at: i64,
+    pub scrobbled: bool, // Já entrou no log de scrobbles
+}
+
+impl ListenSession {
+    pub fn start(path: PathBuf) -> Self {
+        ListenSession { path, started_at: Local::now().timestamp(), scrobbled: false }
+    }
+
+    pub fn started_at(&self) -> i64 {
+        self.started_at
+    }
+
+    pub fn finish(self, played: Duration, completed: bool) -> ListenRecord {
+        ListenRecord { path: self.path, started_at: self.started_at, played, completed }
+    }
+}
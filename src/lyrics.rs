@@ -0,0 +1,193 @@
+// src/lyrics.rs
+// Letras das faixas: arquivo `.lrc` ao lado do áudio ou letras embutidas nas tags
+// (SYLT sincronizado do ID3v2, ou USLT/LYRICS em texto, que muitas vezes também vem em LRC).
+// Linhas com tempo permitem destacar a linha atual e pular para ela.
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use lofty::config::ParseOptions;
+use lofty::id3::v2::{Frame, FrameFlags, FrameId, SynchronizedTextFrame, TimestampFormat};
+use lofty::mpeg::MpegFile;
+use lofty::prelude::*;
+
+use crate::playlist_io::cue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricLine {
+    pub time: Option<Duration>, // None = letra sem sincronização
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lyrics {
+    pub lines: Vec<LyricLine>,
+    pub source: String, // De onde veio (exibido no painel)
+}
+
+impl Lyrics {
+    pub fn is_synced(&self) -> bool {
+        self.lines.iter().any(|line| line.time.is_some())
+    }
+
+    // Última linha cujo tempo já passou
+    pub fn current_line(&self, position: Duration) -> Option<usize> {
+        self.lines.iter().rposition(|line| line.time.is_some_and(|time| time <= position))
+    }
+}
+
+// --- Formato LRC ---
+// `[mm:ss.xx]texto`, com vários tempos na mesma linha, `[offset:+/-ms]` e tempos por
+// palavra (`<mm:ss.xx>`, LRC "enhanced"), que são descartados.
+pub fn parse_lrc(text: &str) -> Vec<LyricLine> {
+    let mut offset_ms: i64 = 0;
+    let mut lines = Vec::new();
+    let mut plain = Vec::new();
+    for raw in text.lines() {
+        let mut rest = raw.trim();
+        let mut times = Vec::new();
+        while let Some(tag) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            let (inside, after) = tag;
+            if let Some(value) = inside.strip_prefix("offset:") {
+                offset_ms = value.trim().parse().unwrap_or(0);
+            } else if let Some(time) = parse_timestamp(inside) {
+                times.push(time);
+            } else if !inside.contains(':') {
+                break; // Colchete que faz parte do texto
+            }
+            rest = after;
+        }
+        let text = strip_word_times(rest).trim().to_string();
+        if times.is_empty() {
+            // Linha de metadado ([ar:...]) consumida inteira fica vazia e é ignorada
+            if rest.len() == raw.trim().len() { plain.push(LyricLine { time: None, text }); }
+            continue;
+        }
+        for time in times { lines.push(LyricLine { time: Some(time), text: text.clone() }); }
+    }
+    if lines.is_empty() { return trim_blank_edges(plain); }
+    // O offset positivo adianta a letra
+    for line in &mut lines {
+        if let Some(time) = line.time {
+            let ms = time.as_millis() as i64 - offset_ms;
+            line.time = Some(Duration::from_millis(ms.max(0) as u64));
+        }
+    }
+    lines.sort_by_key(|line| line.time);
+    lines
+}
+
+// "mm:ss", "mm:ss.xx" ou "mm:ss.xxx"
+fn parse_timestamp(text: &str) -> Option<Duration> {
+    let (minutes, seconds) = text.trim().split_once(':')?;
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.replace(':', ".").parse().ok()?;
+    if !(0.0..60.0).contains(&seconds) { return None; }
+    Some(Duration::from_secs(minutes * 60) + Duration::from_secs_f64(seconds))
+}
+
+fn strip_word_times(text: &str) -> Cow<'_, str> {
+    if !text.contains('<') { return Cow::Borrowed(text); }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        match rest[start..].find('>') {
+            Some(end) if parse_timestamp(&rest[start + 1..start + end]).is_some() => rest = &rest[start + end + 1..],
+            _ => { out.push('<'); rest = &rest[start + 1..]; }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+fn trim_blank_edges(mut lines: Vec<LyricLine>) -> Vec<LyricLine> {
+    while lines.last().is_some_and(|line| line.text.is_empty()) { lines.pop(); }
+    let first = lines.iter().position(|line| !line.text.is_empty()).unwrap_or(lines.len());
+    lines.drain(..first);
+    lines
+}
+
+// --- Carregamento ---
+// Ordem: .lrc ao lado do arquivo, SYLT (MP3), e por fim USLT/LYRICS das tags
+pub fn load(path: &Path) -> Option<Lyrics> {
+    // Faixa de CUE sheet: o .lrc e as tags seriam do álbum inteiro
+    if cue::split_track_path(path).is_some() { return None; }
+    if let Some(lrc_path) = sidecar_path(path) {
+        match fs::read(&lrc_path) {
+            Ok(bytes) => {
+                let lines = parse_lrc(&String::from_utf8_lossy(&bytes));
+                let name = lrc_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                if !lines.is_empty() { return Some(Lyrics { lines, source: name }); }
+            }
+            Err(e) => eprintln!("Lyrics: Failed to read {:?}: {}", lrc_path, e),
+        }
+    }
+    if let Some(lines) = read_sylt(path) {
+        return Some(Lyrics { lines, source: "Embedded (SYLT)".to_string() });
+    }
+    let tagged_file = lofty::read_from_path(path).ok()?;
+    let tag = tagged_file.primary_tag().or_else(|| tagged_file.first_tag())?;
+    let lines = parse_lrc(tag.get_string(&ItemKey::Lyrics)?);
+    if lines.is_empty() { return None; }
+    Some(Lyrics { lines, source: "Embedded".to_string() })
+}
+
+// "Faixa.lrc" (ou ".LRC") na mesma pasta
+fn sidecar_path(path: &Path) -> Option<PathBuf> {
+    ["lrc", "LRC"].iter().map(|ext| path.with_extension(ext)).find(|candidate| candidate.is_file())
+}
+
+fn read_sylt(path: &Path) -> Option<Vec<LyricLine>> {
+    if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("mp3")) { return None; }
+    let mut file = fs::File::open(path).ok()?;
+    let mpeg = MpegFile::read_from(&mut file, ParseOptions::new().read_cover_art(false)).ok()?;
+    let Some(Frame::Binary(frame)) = mpeg.id3v2()?.get(&FrameId::Valid(Cow::Borrowed("SYLT"))) else { return None; };
+    let sylt = SynchronizedTextFrame::parse(&frame.data, FrameFlags::default()).ok()?;
+    // Tempos em frames MPEG dependeriam da taxa do arquivo; praticamente ninguém usa
+    if sylt.timestamp_format != TimestampFormat::MS { return None; }
+    let lines: Vec<LyricLine> = sylt.content.into_iter()
+        .map(|(ms, text)| LyricLine { time: Some(Duration::from_millis(ms as u64)), text: text.trim_matches(['\r', '\n']).to_string() })
+        .collect();
+    if lines.is_empty() { None } else { Some(lines) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_synced_lines_with_offset_and_word_times() {
+        let text = "[ar:Artist]\n[offset:500]\n[00:12.00]First line\n[00:15.50][01:02.25]<00:15.50>Chorus <00:16.00>line\n[00:20.000]\n";
+        let lines = parse_lrc(text);
+        let expected = [
+            (11_500, "First line"),
+            (15_000, "Chorus line"),
+            (19_500, ""),
+            (61_750, "Chorus line"),
+        ];
+        assert_eq!(lines.len(), expected.len());
+        for (line, (ms, text)) in lines.iter().zip(expected) {
+            assert_eq!(line.time, Some(Duration::from_millis(ms)));
+            assert_eq!(line.text, text);
+        }
+    }
+
+    #[test]
+    fn plain_text_is_unsynced() {
+        let lines = parse_lrc("\nFirst verse\n[Chorus]\nSecond verse\n\n");
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, ["First verse", "[Chorus]", "Second verse"]);
+        assert!(lines.iter().all(|line| line.time.is_none()));
+    }
+
+    #[test]
+    fn current_line_follows_position() {
+        let lyrics = Lyrics { lines: parse_lrc("[00:01.00]a\n[00:05.00]b\n[00:09.00]c"), source: String::new() };
+        assert_eq!(lyrics.current_line(Duration::from_millis(500)), None);
+        assert_eq!(lyrics.current_line(Duration::from_secs(5)), Some(1));
+        assert_eq!(lyrics.current_line(Duration::from_secs(60)), Some(2));
+    }
+}
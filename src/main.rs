@@ -1,11 +1,31 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod files;
+mod library;
+mod lyrics;
+mod metadata;
+mod organizer;
+mod playback_clock;
+mod playback_control;
+mod playlist_io;
+mod resample;
+mod sample_tap;
+mod scrobble;
+mod tag_writer;
+mod time_stretch;
 mod ui;
+mod waveform;
 
 use lofty::file::AudioFile;
 use ui::app::{MusicPlayerApp, AudioCommand, AudioResponse};
+use playback_clock::PlaybackClock;
+use playback_control::PlaybackControl;
+use playlist_io::cue;
+use resample::Resample;
+use sample_tap::{SampleTap, TappedSource};
+use time_stretch::TimeStretch;
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -37,6 +57,14 @@ struct SymphoniaSource {
     track_time_base: Option<TimeBase>,
     buffer: SampleBuffer<f32>,
     current_frame_pos: usize,
+    buffer_end: usize, // Amostras do buffer que devem tocar (o pacote pode ser recortado)
+    start_ts: u64, // Frames antes deste ponto são descartados (seek exato)
+    end_ts: Option<u64>, // Fim do trecho (faixa de CUE sheet)
+    clock: Option<Arc<PlaybackClock>>, // Recebe o tempo de cada pacote tocado
+    clock_offset: Duration, // Início da faixa no arquivo (CUE); a posição publicada é relativa a ele
+    control: Option<Arc<PlaybackControl>>, // Laço A-B (posições relativas ao início da faixa)
+    loop_pending: bool, // O pacote atual termina no ponto B: o próximo vem do ponto A
+    last_ts: Option<u64>, // Início do último pacote tocado (None logo depois de um seek)
 }
 
 impl SymphoniaSource {
@@ -47,8 +75,8 @@ impl SymphoniaSource {
         codec_params: &CodecParameters,
     ) -> Result<Self, SymphoniaError> {
         let spec = SignalSpec::new(
-            codec_params.sample_rate.ok_or(SymphoniaError::Unsupported("Missing sample rate".into()))?,
-            codec_params.channels.ok_or(SymphoniaError::Unsupported("Missing channel spec".into()))?,
+            codec_params.sample_rate.ok_or(SymphoniaError::Unsupported("Missing sample rate"))?,
+            codec_params.channels.ok_or(SymphoniaError::Unsupported("Missing channel spec"))?,
         );
         let track_time_base = codec_params.time_base;
         let buffer_capacity = 4096;
@@ -62,14 +90,60 @@ impl SymphoniaSource {
             track_time_base,
             buffer,
             current_frame_pos: 0,
+            buffer_end: 0,
+            start_ts: 0,
+            end_ts: None,
+            clock: None,
+            clock_offset: Duration::ZERO,
+            control: None,
+            loop_pending: false,
+            last_ts: None,
         })
     }
 
+    fn attach_clock(&mut self, clock: Arc<PlaybackClock>, offset: Duration) {
+        self.clock = Some(clock);
+        self.clock_offset = offset;
+    }
+
+    fn attach_control(&mut self, control: Arc<PlaybackControl>) {
+        self.control = Some(control);
+    }
+
+    // Laço A-B em timestamps do arquivo
+    fn loop_region(&self) -> Option<(u64, u64)> {
+        let (start, end) = self.control.as_ref()?.loop_region()?;
+        let tb = self.track_time_base?;
+        let to_ts = |position: Duration| tb.calc_timestamp(duration_to_time(self.clock_offset + position));
+        Some((to_ts(start), to_ts(end)))
+    }
+
+    // Volta para o ponto A sem sair da fonte (o rodio nem percebe o salto)
+    fn seek_loop_start(&mut self) -> bool {
+        let Some((start, _)) = self.control.as_ref().and_then(|control| control.loop_region()) else { return false; };
+        match self.try_seek(self.clock_offset + start) {
+            Ok(_) => { self.last_ts = None; true }
+            Err(e) => { eprintln!("A-B loop: Seek to loop start failed: {}", e); false }
+        }
+    }
+
+    // Fim do arquivo (ou do trecho do CUE) antes do ponto B: se estava dentro do laço, volta para A
+    fn loop_at_end(&mut self) -> bool {
+        match (self.loop_region(), self.last_ts) {
+            (Some((start, end)), Some(last)) if (start..end).contains(&last) => self.seek_loop_start(),
+            _ => false,
+        }
+    }
+
+    // Para a fonte no ponto `end` do arquivo (início da próxima faixa do CUE)
+    fn set_end(&mut self, end: Duration) {
+        if let Some(tb) = self.track_time_base {
+            self.end_ts = Some(tb.calc_timestamp(duration_to_time(end)));
+        }
+    }
+
     fn try_seek(&mut self, time: Duration) -> Result<Time, SymphoniaError> {
-         let total_secs_f64 = time.as_secs_f64();
-         let seconds = total_secs_f64.trunc() as u64;
-         let frac = total_secs_f64.fract();
-         let seek_time = Time::new(seconds, frac);
+         let seek_time = duration_to_time(time);
 
          let seek_result = self.reader.seek(
              SeekMode::Accurate,
@@ -77,11 +151,15 @@ impl SymphoniaSource {
          )?;
 
          self.decoder.reset();
-         self.current_frame_pos = self.buffer.len();
-         println!("Symphonia seek completed to raw ts: {}", seek_result.actual_ts);
+         self.current_frame_pos = 0;
+         self.buffer_end = 0;
+         self.loop_pending = false;
+         // O seek cai no início de um pacote; o trecho até o ponto pedido é descartado na decodificação
+         self.start_ts = seek_result.required_ts.max(seek_result.actual_ts);
+         println!("Symphonia seek completed to raw ts: {} (required {})", seek_result.actual_ts, seek_result.required_ts);
 
          if let Some(tb) = self.track_time_base {
-             let actual_time = tb.calc_time(seek_result.actual_ts);
+             let actual_time = tb.calc_time(self.start_ts);
              Ok(actual_time)
          } else {
              // Usar SymphoniaError::Unsupported quando time_base está faltando
@@ -93,10 +171,15 @@ impl SymphoniaSource {
     }
 
     fn decode_next_frame(&mut self) -> Result<bool, SymphoniaError> {
+        if self.loop_pending {
+            self.loop_pending = false;
+            self.seek_loop_start();
+        }
         loop {
             let packet = match self.reader.next_packet() {
                 Ok(packet) => packet,
                 Err(SymphoniaError::IoError(ref err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    if self.loop_at_end() { continue; }
                     return Ok(false);
                 }
                 Err(err) => {
@@ -108,6 +191,10 @@ impl SymphoniaSource {
             if packet.track_id() != self.track_id {
                 continue;
             }
+            if self.end_ts.is_some_and(|end| packet.ts() >= end) {
+                if self.loop_at_end() { continue; }
+                return Ok(false);
+            }
 
             match self.decoder.decode(&packet) {
                 Ok(decoded) => {
@@ -117,8 +204,30 @@ impl SymphoniaSource {
                         self.buffer = SampleBuffer::<f32>::new(buffer_capacity as u64, self.spec);
                         println!("Spec changed during decoding!");
                     }
+                    let frames = decoded.frames() as u64;
                     self.buffer.copy_interleaved_ref(decoded);
-                    self.current_frame_pos = 0;
+                    // Recorta o pacote entre start_ts e end_ts
+                    let channels = self.spec.channels.count();
+                    let skip = self.start_ts.saturating_sub(packet.ts()).min(frames);
+                    let mut keep = self.end_ts.map_or(frames, |end| end.saturating_sub(packet.ts()).min(frames));
+                    if skip >= keep { continue; }
+                    // Pacote que alcança o ponto B: toca até B e o próximo vem de A.
+                    // Quem começou depois de B (seek para fora do laço) segue normalmente
+                    if let Some((_, loop_end)) = self.loop_region() {
+                        if packet.ts() + skip < loop_end && packet.ts() + keep >= loop_end {
+                            keep = loop_end - packet.ts();
+                            self.loop_pending = true;
+                        }
+                    }
+                    self.last_ts = Some(packet.ts() + skip);
+                    self.current_frame_pos = skip as usize * channels;
+                    self.buffer_end = keep as usize * channels;
+                    if let Some(clock) = &self.clock {
+                        clock.add_played(Duration::from_secs_f64((keep - skip) as f64 / self.spec.rate as f64));
+                        if let Some(tb) = self.track_time_base {
+                            clock.set_position(time_to_duration(tb.calc_time(packet.ts() + skip)).saturating_sub(self.clock_offset));
+                        }
+                    }
                     return Ok(true);
                 }
                 Err(SymphoniaError::DecodeError(err)) => {
@@ -136,7 +245,7 @@ impl SymphoniaSource {
 
 // --- Implementações Source e Iterator ---
 impl Source for SymphoniaSource {
-    #[inline] fn current_frame_len(&self) -> Option<usize> { Some(self.buffer.len() - self.current_frame_pos) }
+    #[inline] fn current_frame_len(&self) -> Option<usize> { Some(self.buffer_end.saturating_sub(self.current_frame_pos)) }
     #[inline] fn channels(&self) -> u16 { self.spec.channels.count() as u16 }
     #[inline] fn sample_rate(&self) -> u32 { self.spec.rate }
     #[inline] fn total_duration(&self) -> Option<Duration> { None } // Mantém Lofty como primário
@@ -145,14 +254,14 @@ impl Iterator for SymphoniaSource {
     type Item = f32;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_frame_pos < self.buffer.len() {
+        if self.current_frame_pos < self.buffer_end {
             let sample = self.buffer.samples()[self.current_frame_pos];
             self.current_frame_pos += 1;
             Some(sample)
         } else {
             match self.decode_next_frame() {
                 Ok(true) => {
-                    if self.current_frame_pos < self.buffer.len() {
+                    if self.current_frame_pos < self.buffer_end {
                          let sample = self.buffer.samples()[self.current_frame_pos];
                          self.current_frame_pos += 1;
                          Some(sample)
@@ -175,12 +284,12 @@ fn main() -> Result<(), eframe::Error> {
 
     let options = eframe::NativeOptions {
         persist_window: true,
-        viewport: egui::ViewportBuilder::default().with_inner_size([400.0, 500.0]).with_title("Rust Music Player Lite" ),
+        viewport: egui::ViewportBuilder::default().with_inner_size([720.0, 560.0]).with_title("Rust Music Player Lite" ).with_drag_and_drop(true),
         ..Default::default()
     };
 
     eframe::run_native(
-        "Rust Music Player Lite",
+        library::APP_NAME,
         options,
         Box::new(move |cc| {
             let sink = match Sink::try_new(&stream_handle) {
@@ -189,6 +298,8 @@ fn main() -> Result<(), eframe::Error> {
             };
             let (cmd_tx, cmd_rx): (CrossbeamSender<AudioCommand>, CrossbeamReceiver<AudioCommand>) = unbounded();
             let (resp_tx, resp_rx): (CrossbeamSender<AudioResponse>, CrossbeamReceiver<AudioResponse>) = unbounded();
+            // Decodificadores de imagem (capas no editor de tags)
+            egui_extras::install_image_loaders(&cc.egui_ctx);
 
             let mut app: MusicPlayerApp = if let Some(storage) = cc.storage {
                 eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
@@ -198,10 +309,15 @@ fn main() -> Result<(), eframe::Error> {
 
             let sink_clone = Arc::clone(&sink);
             let resp_tx_clone = resp_tx.clone();
+            let playback_clock = Arc::new(PlaybackClock::default());
+            let clock_clone = Arc::clone(&playback_clock);
+            let sample_tap = Arc::new(SampleTap::default());
+            let tap_clone = Arc::clone(&sample_tap);
             thread::spawn(move || {
                 println!("Audio thread started.");
-                let mut current_audio_index: Option<usize> = None;
                 let mut current_path_buf: Option<PathBuf> = None;
+                // Laço A-B, velocidade e tom, lidos pelas fontes que estão no Sink
+                let control = Arc::new(PlaybackControl::default());
 
                 loop {
                     let command = match cmd_rx.recv_timeout(Duration::from_millis(100)) {
@@ -212,9 +328,9 @@ fn main() -> Result<(), eframe::Error> {
 
                     { // Check track end
                         let sink_guard = sink_clone.lock().expect("Audio thread failed to acquire sink lock for end check");
-                        if sink_guard.empty() && current_audio_index.is_some() {
-                            println!("Audio thread: Detected track end (sink empty) for index {:?}", current_audio_index);
-                            current_audio_index = None; current_path_buf = None;
+                        if sink_guard.empty() && current_path_buf.is_some() {
+                            println!("Audio thread: Detected track end (sink empty) for {:?}", current_path_buf);
+                            current_path_buf = None;
                             if resp_tx_clone.send(AudioResponse::PlaybackEnded).is_err() { eprintln!("Audio thread: Failed to send PlaybackEnded response (UI likely closed)."); break; }
                             continue;
                         }
@@ -223,29 +339,27 @@ fn main() -> Result<(), eframe::Error> {
                     if let Some(command) = command {
                         match command {
                             AudioCommand::PlayTrack(path_buf, index) => {
-                                println!("Audio thread: Received PlayTrack command for index {}", index);
+                                println!("Audio thread: Received PlayTrack command for index {:?}", index);
                                 let sink_guard = sink_clone.lock().expect("Audio thread failed to acquire sink lock for PlayTrack");
                                 if !sink_guard.empty() { sink_guard.stop(); }
 
-                                let load_result = load_symphonia_source(&path_buf);
+                                clock_clone.reset();
+                                control.set_loop(None); // O laço é da faixa anterior
+                                let load_result = load_track(&path_buf, Duration::ZERO, &clock_clone);
                                 match load_result {
-                                    Ok(new_source) => {
-                                        let mut track_duration: Option<Duration> = None;
-                                        match lofty::read_from_path(&path_buf) {
-                                            Ok(tagged_file) => { track_duration = Some(tagged_file.properties().duration()); }
-                                            Err(e) => { eprintln!("Audio thread: Error reading metadata with Lofty: {}", e); }
-                                        }
+                                    Ok(loaded) => {
+                                        let track_duration = loaded.duration;
                                         println!("Audio thread: Track duration: {:?}", track_duration);
-                                        current_path_buf = Some(path_buf.clone()); current_audio_index = Some(index);
-                                        sink_guard.append(new_source); sink_guard.play(); drop(sink_guard);
+                                        current_path_buf = Some(path_buf.clone());
+                                        sink_guard.append(output_source(loaded.source, &control, &tap_clone)); sink_guard.play(); drop(sink_guard);
                                         if resp_tx_clone.send(AudioResponse::PlaybackStarted).is_err() { break; }
-                                        if resp_tx_clone.send(AudioResponse::CurrentlyPlaying(Some(index), track_duration)).is_err() { break; }
+                                        if resp_tx_clone.send(AudioResponse::CurrentlyPlaying(Some(path_buf), index, track_duration)).is_err() { break; }
                                     }
                                     Err(err_msg) => {
                                         drop(sink_guard); eprintln!("Audio thread: Error loading track with Symphonia {:?}: {}", path_buf, err_msg);
-                                        current_audio_index = None; current_path_buf = None;
+                                        current_path_buf = None;
                                         if resp_tx_clone.send(AudioResponse::LoadError(path_buf.clone(), err_msg)).is_err() { break; }
-                                        if resp_tx_clone.send(AudioResponse::CurrentlyPlaying(None, None)).is_err() { break; }
+                                        if resp_tx_clone.send(AudioResponse::CurrentlyPlaying(None, None, None)).is_err() { break; }
                                     }
                                 }
                             }
@@ -266,39 +380,36 @@ fn main() -> Result<(), eframe::Error> {
                             AudioCommand::Stop => {
                                 println!("Audio thread: Received Stop command.");
                                 { let sink_guard = sink_clone.lock().expect("Audio thread failed to acquire sink lock for Stop"); if !sink_guard.empty() { sink_guard.stop(); } }
-                                current_audio_index = None; current_path_buf = None;
+                                current_path_buf = None;
                                 if resp_tx_clone.send(AudioResponse::PlaybackStopped).is_err() { break; }
                             }
                             AudioCommand::SetVolume(new_volume) => {
                                 let sink_guard = sink_clone.lock().expect("Audio thread failed to acquire sink lock for SetVolume");
                                 sink_guard.set_volume(new_volume.clamp(0.0, 1.0));
+                                tap_clone.set_volume(new_volume.clamp(0.0, 1.0));
+                            }
+                            AudioCommand::SetLoop(region) => {
+                                println!("Audio thread: A-B loop set to {:?}", region);
+                                control.set_loop(region);
                             }
+                            AudioCommand::SetSpeed(speed) => control.set_speed(speed),
+                            AudioCommand::SetPreservePitch(preserve) => control.set_preserve_pitch(preserve),
+                            AudioCommand::SetPitchShift(semitones) => control.set_pitch_shift(semitones),
                             AudioCommand::Seek(target_duration) => {
                                 println!("Audio thread: Received Seek command (Symphonia) to {:?}", target_duration);
                                 if let Some(path_to_reload) = current_path_buf.clone() {
                                     let sink_guard = sink_clone.lock().expect("Audio thread failed to acquire sink lock for Seek");
                                     if !sink_guard.empty() { sink_guard.stop(); }
 
-                                    match load_symphonia_source(&path_to_reload) {
-                                        Ok(mut new_source) => {
-                                            println!("Audio thread: Reloaded source for seek.");
-                                            match new_source.try_seek(target_duration) {
-                                                Ok(actual_time) => {
-                                                    println!("Audio thread: Symphonia seek successful to actual time: {:?}", actual_time);
-                                                    sink_guard.append(new_source); sink_guard.play(); drop(sink_guard);
-                                                    let actual_duration = Duration::from_secs_f64(actual_time.seconds as f64 + actual_time.frac);
-                                                    if resp_tx_clone.send(AudioResponse::SeekCompleted(actual_duration)).is_err() { eprintln!("Audio thread: Failed to send SeekCompleted response."); break; }
-                                                }
-                                                Err(seek_err) => {
-                                                    drop(sink_guard); eprintln!("Audio thread: Symphonia seek failed within source: {}", seek_err);
-                                                    current_audio_index = None; current_path_buf = None;
-                                                    if resp_tx_clone.send(AudioResponse::PlaybackStopped).is_err() { break; }
-                                                }
-                                            }
+                                    match load_track(&path_to_reload, target_duration, &clock_clone) {
+                                        Ok(loaded) => {
+                                            println!("Audio thread: Symphonia seek successful to position: {:?}", loaded.position);
+                                            sink_guard.append(output_source(loaded.source, &control, &tap_clone)); sink_guard.play(); drop(sink_guard);
+                                            if resp_tx_clone.send(AudioResponse::SeekCompleted(loaded.position)).is_err() { eprintln!("Audio thread: Failed to send SeekCompleted response."); break; }
                                         }
                                         Err(err_msg) => {
                                             drop(sink_guard); eprintln!("Audio thread: Failed to reload source for seek: {}", err_msg);
-                                            current_audio_index = None; current_path_buf = None;
+                                            current_path_buf = None;
                                             if resp_tx_clone.send(AudioResponse::PlaybackStopped).is_err() { break; }
                                         }
                                     }
@@ -310,12 +421,63 @@ fn main() -> Result<(), eframe::Error> {
                 println!("Audio thread finished.");
             });
 
-            app.setup(stream_handle, sink, cmd_tx, resp_rx);
+            app.setup(stream_handle, sink, cmd_tx, resp_rx, playback_clock, sample_tap);
             Box::new(app)
         }),
     )
 }
 
+// --- Carregamento de uma faixa da playlist ---
+// Uma faixa de CUE sheet é um trecho de outro arquivo: a fonte começa no INDEX da faixa,
+// para no INDEX da próxima, e as posições (seek, duração) são relativas ao trecho.
+struct LoadedTrack {
+    source: SymphoniaSource,
+    duration: Option<Duration>,
+    position: Duration, // Posição real depois do seek, relativa ao início da faixa
+}
+
+fn load_track(path: &Path, position: Duration, clock: &Arc<PlaybackClock>) -> Result<LoadedTrack, String> {
+    let (file_path, start, end) = match cue::read_track(path) {
+        Some(track) => { let track = track?; (track.file, track.start, track.end) }
+        None => (path.to_path_buf(), Duration::ZERO, None),
+    };
+
+    let mut source = load_symphonia_source(&file_path)?;
+    let file_duration = match lofty::read_from_path(&file_path) {
+        Ok(tagged_file) => Some(tagged_file.properties().duration()),
+        Err(e) => { eprintln!("Audio thread: Error reading metadata with Lofty: {}", e); None }
+    };
+    let duration = end.or(file_duration).map(|end| end.saturating_sub(start));
+
+    let mut actual_position = Duration::ZERO;
+    if !(start + position).is_zero() {
+        let actual_time = source.try_seek(start + position).map_err(|e| format!("Seek failed: {}", e))?;
+        actual_position = time_to_duration(actual_time).saturating_sub(start);
+    }
+    if let Some(end) = end { source.set_end(end); }
+    source.attach_clock(Arc::clone(clock), start);
+    clock.set_position(actual_position);
+    Ok(LoadedTrack { source, duration, position: actual_position })
+}
+
+// Cadeia que vai para o Sink: decodificador (com o laço A-B) → andamento → reamostragem → tap da UI
+type OutputSource = TappedSource<Resample<TimeStretch<SymphoniaSource>>>;
+
+fn output_source(mut source: SymphoniaSource, control: &Arc<PlaybackControl>, tap: &Arc<SampleTap>) -> OutputSource {
+    source.attach_control(Arc::clone(control));
+    let stretched = TimeStretch::new(source, Arc::clone(control));
+    TappedSource::new(Resample::new(stretched, Arc::clone(control)), Arc::clone(tap))
+}
+
+fn duration_to_time(duration: Duration) -> Time {
+    let total_secs_f64 = duration.as_secs_f64();
+    Time::new(total_secs_f64.trunc() as u64, total_secs_f64.fract())
+}
+
+fn time_to_duration(time: Time) -> Duration {
+    Duration::from_secs_f64(time.seconds as f64 + time.frac)
+}
+
 // --- Função load_symphonia_source ---
 fn load_symphonia_source(file_path: &PathBuf) -> Result<SymphoniaSource, String> {
     let file = File::open(file_path).map_err(|e| format!("Failed to open file: {}", e))?;
@@ -0,0 +1,165 @@
+// src/metadata.rs
+// Cache de metadados (tags) das faixas, lidos com Lofty em uma thread separada
+// para não travar a UI quando a playlist tem centenas de entradas.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{unbounded, Receiver as CrossbeamReceiver, Sender as CrossbeamSender};
+use lofty::file::FileType;
+use lofty::prelude::*;
+
+use crate::playlist_io::cue::{self, CueTrack};
+
+// --- Informações de uma faixa ---
+#[derive(Debug, Clone, Default)]
+pub struct TrackInfo {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+    pub genre: Option<String>,
+    pub duration: Option<Duration>,
+    pub bitrate: Option<u32>, // kbps
+    pub format: Option<String>,
+}
+
+impl TrackInfo {
+    pub fn read(path: &Path) -> Self {
+        match cue::read_track(path) {
+            Some(Ok(track)) => return TrackInfo::read_cue_track(&track),
+            Some(Err(e)) => { eprintln!("Metadata: {}", e); return TrackInfo::default(); }
+            None => {}
+        }
+        let mut info = TrackInfo::default();
+        match lofty::read_from_path(path) {
+            Ok(tagged_file) => {
+                let properties = tagged_file.properties();
+                info.duration = Some(properties.duration());
+                info.bitrate = properties.audio_bitrate().or(properties.overall_bitrate());
+                info.format = Some(format_name(tagged_file.file_type()));
+                if let Some(tag) = tagged_file.primary_tag().or_else(|| tagged_file.first_tag()) {
+                    info.title = tag.title().map(|s| s.into_owned());
+                    info.artist = tag.artist().map(|s| s.into_owned());
+                    info.album = tag.album().map(|s| s.into_owned());
+                    info.track_number = tag.track();
+                    info.genre = tag.genre().map(|s| s.into_owned());
+                }
+            }
+            Err(e) => { eprintln!("Metadata: Failed to read tags from {:?}: {}", path, e); }
+        }
+        info
+    }
+
+    // Faixa de CUE sheet: formato e bitrate vêm do arquivo de áudio, o resto do CUE
+    fn read_cue_track(track: &CueTrack) -> Self {
+        let file_info = TrackInfo::read(&track.file);
+        TrackInfo {
+            title: track.title.clone(),
+            artist: track.performer.clone(),
+            album: track.album.clone(),
+            track_number: Some(track.number),
+            duration: track.duration(file_info.duration),
+            ..file_info
+        }
+    }
+}
+
+// Nome amigável do formato do arquivo
+fn format_name(file_type: FileType) -> String {
+    match file_type {
+        FileType::Mpeg => "MP3".to_string(),
+        FileType::Flac => "FLAC".to_string(),
+        FileType::Vorbis => "Ogg Vorbis".to_string(),
+        FileType::Opus => "Opus".to_string(),
+        FileType::Wav => "WAV".to_string(),
+        FileType::Mp4 => "MP4/M4A".to_string(),
+        FileType::Aiff => "AIFF".to_string(),
+        FileType::Ape => "APE".to_string(),
+        FileType::WavPack => "WavPack".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+// --- Cache com leitura em segundo plano ---
+#[derive(Default)]
+pub struct MetadataCache {
+    entries: HashMap<PathBuf, TrackInfo>,
+    pending: HashSet<PathBuf>,
+    request_sender: Option<CrossbeamSender<PathBuf>>,
+    result_receiver: Option<CrossbeamReceiver<(PathBuf, TrackInfo)>>,
+    // Aumenta a cada leitura guardada ou descartada
+    revision: u64,
+}
+
+impl MetadataCache {
+    // A thread de leitura só é criada no primeiro pedido
+    fn ensure_worker(&mut self) {
+        if self.request_sender.is_some() { return; }
+        let (req_tx, req_rx): (CrossbeamSender<PathBuf>, CrossbeamReceiver<PathBuf>) = unbounded();
+        let (res_tx, res_rx) = unbounded();
+        thread::spawn(move || {
+            for path in req_rx.iter() {
+                let info = TrackInfo::read(&path);
+                if res_tx.send((path, info)).is_err() { break; }
+            }
+        });
+        self.request_sender = Some(req_tx);
+        self.result_receiver = Some(res_rx);
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&TrackInfo> {
+        self.entries.get(path)
+    }
+
+    // Lê as tags imediatamente (bloqueante) se ainda não estiverem no cache.
+    // Usado quando a informação é necessária na hora, como ao exportar a playlist.
+    pub fn load_now(&mut self, path: &Path) -> &TrackInfo {
+        if !self.entries.contains_key(path) {
+            let info = TrackInfo::read(path);
+            self.entries.insert(path.to_path_buf(), info);
+            self.revision += 1;
+        }
+        &self.entries[path]
+    }
+
+    // Descarta os metadados de uma faixa (ex.: tags editadas) para serem lidos de novo
+    pub fn invalidate(&mut self, path: &Path) {
+        if self.entries.remove(path).is_some() { self.revision += 1; }
+    }
+
+    // Pede a leitura das tags se ainda não estiverem no cache
+    pub fn request(&mut self, path: &Path) {
+        if self.entries.contains_key(path) || self.pending.contains(path) { return; }
+        self.ensure_worker();
+        if let Some(sender) = &self.request_sender {
+            if sender.send(path.to_path_buf()).is_ok() {
+                self.pending.insert(path.to_path_buf());
+            }
+        }
+    }
+
+    // Recolhe os resultados prontos. Retorna true se algo mudou.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        if let Some(receiver) = &self.result_receiver {
+            while let Ok((path, info)) = receiver.try_recv() {
+                self.pending.remove(&path);
+                self.entries.insert(path, info);
+                self.revision += 1;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
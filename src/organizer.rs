@@ -0,0 +1,397 @@
+// src/organizer.rs
+// Organizador: renomeia e move arquivos de acordo com um modelo montado a partir das tags,
+// como `{albumartist}/{year} - {album}/{disc}{track:02} {title}.{ext}`. Primeiro é feito um
+// plano (prévia, sem tocar no disco) com as colisões marcadas; só depois os arquivos são
+// movidos. Cada lote movido vai para um log no disco, que permite desfazer o último lote.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::library::APP_NAME;
+use crate::tag_writer::TagFields;
+
+pub const DEFAULT_TEMPLATE: &str = "{albumartist}/{year} - {album}/{disc}{track:02} {title}.{ext}";
+pub const TEMPLATE_FIELDS: [&str; 10] = ["title", "artist", "albumartist", "album", "year", "track", "disc", "genre", "filename", "ext"];
+const UNDO_LOG_FILE: &str = "organize_undo.json";
+// Lotes antigos saem do log; só o último pode ser desfeito, os outros ficam como registro
+const UNDO_LOG_LIMIT: usize = 20;
+
+// Pares (de, para) de arquivos movidos
+pub type FileMoves = Vec<(PathBuf, PathBuf)>;
+
+// --- Modelo ---
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    Field { name: String, width: Option<usize> }, // {track:02} = número com 2 dígitos
+}
+
+fn parse_template(template: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if start > 0 { pieces.push(Piece::Text(rest[..start].to_string())); }
+        let end = rest[start..].find('}').ok_or("Unclosed '{' in template")? + start;
+        let inner = &rest[start + 1..end];
+        let (name, width) = match inner.split_once(':') {
+            Some((name, width)) => (name, Some(width.parse::<usize>().map_err(|_| format!("Invalid width in {{{}}}", inner))?)),
+            None => (inner, None),
+        };
+        let name = name.trim().to_lowercase();
+        if !TEMPLATE_FIELDS.contains(&name.as_str()) { return Err(format!("Unknown field {{{}}}", name)); }
+        pieces.push(Piece::Field { name, width });
+        rest = &rest[end + 1..];
+    }
+    if rest.contains('}') { return Err("Unmatched '}' in template".to_string()); }
+    if !rest.is_empty() { pieces.push(Piece::Text(rest.to_string())); }
+    Ok(pieces)
+}
+
+// Valor de um campo já limpo para caber em um nome de arquivo
+fn field_value(name: &str, width: Option<usize>, tags: &TagFields, source: &Path) -> String {
+    let stem = source.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let or = |value: &str, fallback: &str| if value.trim().is_empty() { fallback.to_string() } else { value.trim().to_string() };
+    let value = match name {
+        "title" => or(&tags.title, &stem),
+        "artist" => or(&tags.artist, "Unknown Artist"),
+        "albumartist" => or(&tags.album_artist, &or(&tags.artist, "Unknown Artist")),
+        "album" => or(&tags.album, "Unknown Album"),
+        "year" => tags.year.trim().to_string(),
+        "track" => tags.track.trim().to_string(),
+        "disc" => tags.disc.trim().to_string(),
+        "genre" => tags.genre.trim().to_string(),
+        "filename" => stem,
+        "ext" => source.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default(),
+        _ => String::new(),
+    };
+    let value = match (width, value.parse::<u32>()) {
+        (Some(width), Ok(number)) => format!("{:0width$}", number, width = width),
+        _ => value,
+    };
+    sanitize(&value)
+}
+
+// Tira separadores e caracteres que Windows/macOS não aceitam em nomes
+fn sanitize(value: &str) -> String {
+    value.chars().map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { '_' } else { c }).collect()
+}
+
+// Caminho relativo gerado pelo modelo. Pastas que ficam vazias (ex.: sem ano) são puladas.
+fn render(pieces: &[Piece], tags: &TagFields, source: &Path) -> Result<PathBuf, String> {
+    let text: String = pieces.iter().map(|piece| match piece {
+        Piece::Text(text) => text.clone(),
+        Piece::Field { name, width } => field_value(name, *width, tags, source),
+    }).collect();
+    let mut path = PathBuf::new();
+    for part in text.split(['/', '\\']) {
+        // Espaços, pontos e hífens soltos nas pontas (ex.: " - Album" sem ano)
+        let part = part.trim_matches(|c: char| c.is_whitespace() || c == '-').trim_end_matches('.').trim();
+        if part.is_empty() { continue; }
+        path.push(part);
+    }
+    match path.file_name() {
+        Some(name) if Path::new(name).extension().is_some() => Ok(path),
+        _ => Err("Template does not produce a file name with an extension".to_string()),
+    }
+}
+
+// --- Plano (prévia) ---
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveStatus {
+    Move,
+    Unchanged,
+    Collision(String),
+    Skipped(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub status: MoveStatus,
+}
+
+// Monta o plano para as faixas (com as tags já lidas). `in_use` é o arquivo aberto na
+// thread de áudio, que não pode ser movido enquanto toca.
+pub fn plan(sources: &[(PathBuf, Result<TagFields, String>)], template: &str, root: &Path, in_use: Option<&Path>) -> Result<Vec<PlannedMove>, String> {
+    let pieces = parse_template(template)?;
+    let mut planned: Vec<PlannedMove> = sources.iter().map(|(from, tags)| {
+        let skipped = |reason: String| PlannedMove { from: from.clone(), to: from.clone(), status: MoveStatus::Skipped(reason) };
+        if in_use == Some(from.as_path()) { return skipped("Playing now".to_string()); }
+        let tags = match tags { Ok(tags) => tags, Err(e) => return skipped(e.clone()) };
+        match render(&pieces, tags, from) {
+            Ok(relative) => {
+                let to = root.join(relative);
+                let status = if to == *from { MoveStatus::Unchanged } else { MoveStatus::Move };
+                PlannedMove { from: from.clone(), to, status }
+            }
+            Err(e) => skipped(e),
+        }
+    }).collect();
+
+    // Dois arquivos para o mesmo destino (sem diferenciar maiúsculas: vale em qualquer sistema)
+    let mut targets: HashMap<String, usize> = HashMap::new();
+    for entry in planned.iter().filter(|m| matches!(m.status, MoveStatus::Move | MoveStatus::Unchanged)) {
+        *targets.entry(collision_key(&entry.to)).or_default() += 1;
+    }
+    for entry in planned.iter_mut().filter(|m| m.status == MoveStatus::Move) {
+        if targets[&collision_key(&entry.to)] > 1 {
+            entry.status = MoveStatus::Collision("Another file gets the same name".to_string());
+        } else if entry.to.exists() && !is_same_file(&entry.from, &entry.to) {
+            entry.status = MoveStatus::Collision("A file with this name already exists".to_string());
+        }
+    }
+    Ok(planned)
+}
+
+fn collision_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+// Destino que já existe mas é o próprio arquivo (só muda maiúscula/minúscula em sistema que não diferencia)
+fn is_same_file(from: &Path, to: &Path) -> bool {
+    match (fs::canonicalize(from), fs::canonicalize(to)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+// --- Execução ---
+// Move os arquivos do plano que têm status Move, junto com o .lrc ao lado de cada um.
+// `in_use` é conferido de novo: a faixa tocando pode ter mudado desde a prévia.
+// Devolve os pares (de, para) das faixas movidas.
+pub fn execute(planned: &[PlannedMove], root: &Path, in_use: Option<&Path>) -> (FileMoves, Vec<String>) {
+    let mut moved = Vec::new();
+    let mut sidecars = Vec::new();
+    let mut errors = Vec::new();
+    for entry in planned.iter().filter(|m| m.status == MoveStatus::Move) {
+        if in_use == Some(entry.from.as_path()) {
+            errors.push(format!("{:?} is playing now", entry.from));
+            continue;
+        }
+        // O disco pode ter mudado desde a prévia
+        if entry.to.exists() && !is_same_file(&entry.from, &entry.to) {
+            errors.push(format!("{:?} already exists", entry.to));
+            continue;
+        }
+        match move_file(&entry.from, &entry.to) {
+            Ok(()) => {
+                if let Some((lrc_from, lrc_to)) = sidecar_move(&entry.from, &entry.to) {
+                    match move_sidecar(&lrc_from, &lrc_to) {
+                        Ok(()) => sidecars.push((lrc_from, lrc_to)),
+                        Err(e) => errors.push(e),
+                    }
+                }
+                remove_empty_dirs(entry.from.parent(), root);
+                moved.push((entry.from.clone(), entry.to.clone()));
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    if !moved.is_empty() {
+        let batch = OrganizeBatch { root: root.to_path_buf(), at: chrono::Utc::now().timestamp(), moves: moved.clone(), sidecars };
+        if let Err(e) = UndoLog::append(batch) {
+            errors.push(format!("Files were moved, but the undo log could not be saved: {}", e));
+        }
+    }
+    (moved, errors)
+}
+
+// Letra sincronizada ao lado do arquivo (mesmo nome, extensão .lrc), como o player procura
+fn sidecar_move(from: &Path, to: &Path) -> Option<(PathBuf, PathBuf)> {
+    ["lrc", "LRC"].iter()
+        .map(|ext| (from.with_extension(ext), to.with_extension(ext)))
+        .find(|(lrc_from, _)| lrc_from.is_file())
+}
+
+fn move_sidecar(from: &Path, to: &Path) -> Result<(), String> {
+    if to.exists() { return Err(format!("{:?} already exists; lyrics left at {:?}", to, from)); }
+    move_file(from, to)
+}
+
+// rename não atravessa sistemas de arquivos; nesse caso copia e apaga o original
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    }
+    if fs::rename(from, to).is_ok() { return Ok(()); }
+    fs::copy(from, to).map_err(|e| format!("Failed to move {:?} to {:?}: {}", from, to, e))?;
+    fs::remove_file(from).map_err(|e| {
+        let _ = fs::remove_file(to);
+        format!("Failed to remove {:?} after copying: {}", from, e)
+    })
+}
+
+// Apaga as pastas que ficaram vazias, subindo até a pasta raiz (exclusive).
+// remove_dir só apaga pasta vazia, então nada com conteúdo é perdido.
+fn remove_empty_dirs(dir: Option<&Path>, root: &Path) {
+    let mut dir = dir;
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) { break; }
+        if fs::remove_dir(current).is_err() { break; }
+        dir = current.parent();
+    }
+}
+
+// --- Log para desfazer ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizeBatch {
+    pub root: PathBuf,
+    pub at: i64, // Unix timestamp (segundos)
+    pub moves: FileMoves,
+    // .lrc movidos junto com as faixas; ficam fora de `moves` porque o player não guarda esses caminhos
+    #[serde(default)]
+    pub sidecars: FileMoves,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct UndoLog {
+    batches: Vec<OrganizeBatch>,
+}
+
+impl UndoLog {
+    fn file_path() -> Option<PathBuf> {
+        eframe::storage_dir(APP_NAME).map(|dir| dir.join(UNDO_LOG_FILE))
+    }
+
+    fn load() -> UndoLog {
+        UndoLog::file_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = UndoLog::file_path().ok_or("No data directory available")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize undo log: {}", e))?;
+        fs::write(&path, text).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    fn append(batch: OrganizeBatch) -> Result<(), String> {
+        let mut log = UndoLog::load();
+        log.batches.push(batch);
+        let excess = log.batches.len().saturating_sub(UNDO_LOG_LIMIT);
+        log.batches.drain(..excess);
+        log.save()
+    }
+
+    // Último lote que ainda pode ser desfeito
+    pub fn last_batch() -> Option<OrganizeBatch> {
+        UndoLog::load().batches.pop()
+    }
+
+    // Move de volta os arquivos do último lote (na ordem inversa). Devolve os pares
+    // (de, para) das faixas desfeitas; o que falhar continua no log para uma nova tentativa.
+    pub fn undo_last() -> Result<(FileMoves, Vec<String>), String> {
+        let mut log = UndoLog::load();
+        let batch = log.batches.pop().ok_or("Nothing to undo")?;
+        let mut errors = Vec::new();
+        // Os .lrc primeiro, para que as pastas de destino fiquem vazias quando as faixas saírem
+        let (_, failed_sidecars) = move_back(&batch.sidecars, &batch.root, &mut errors);
+        let (restored, failed) = move_back(&batch.moves, &batch.root, &mut errors);
+        if !failed.is_empty() || !failed_sidecars.is_empty() {
+            log.batches.push(OrganizeBatch { moves: failed, sidecars: failed_sidecars, ..batch });
+        }
+        log.save()?;
+        Ok((restored, errors))
+    }
+}
+
+// Desfaz os pares (de, para) na ordem inversa. Devolve os pares (para, de) desfeitos e,
+// na ordem original, os que falharam.
+fn move_back(moves: &[(PathBuf, PathBuf)], root: &Path, errors: &mut Vec<String>) -> (FileMoves, FileMoves) {
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    for (from, to) in moves.iter().rev() {
+        let result = if from.exists() { Err(format!("{:?} already exists", from)) } else { move_file(to, from) };
+        match result {
+            Ok(()) => {
+                remove_empty_dirs(to.parent(), root);
+                restored.push((to.clone(), from.clone()));
+            }
+            Err(e) => {
+                errors.push(e);
+                failed.push((from.clone(), to.clone()));
+            }
+        }
+    }
+    failed.reverse();
+    (restored, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(title: &str, artist: &str, album: &str, year: &str, track: &str) -> TagFields {
+        TagFields {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            year: year.to_string(),
+            track: track.to_string(),
+            ..TagFields::default()
+        }
+    }
+
+    fn rendered(template: &str, tags: &TagFields, source: &str) -> Result<PathBuf, String> {
+        render(&parse_template(template)?, tags, Path::new(source))
+    }
+
+    #[test]
+    fn pads_numbers_to_the_given_width() {
+        let tags = tags("Song", "Artist", "Album", "1999", "3");
+        assert_eq!(rendered("{track:02} {title}.{ext}", &tags, "/in/a.MP3").unwrap(), PathBuf::from("03 Song.mp3"));
+        // Valor que não é número fica como está
+        let tags = TagFields { track: "A1".to_string(), ..tags };
+        assert_eq!(rendered("{track:02} {title}.{ext}", &tags, "/in/a.mp3").unwrap(), PathBuf::from("A1 Song.mp3"));
+    }
+
+    #[test]
+    fn drops_folders_left_empty_by_missing_fields() {
+        let tags = tags("Song", "Artist", "Album", "", "1");
+        assert_eq!(rendered(DEFAULT_TEMPLATE, &tags, "/in/a.flac").unwrap(), PathBuf::from("Artist/Album/01 Song.flac"));
+        assert_eq!(rendered("{genre}/{title}.{ext}", &tags, "/in/a.flac").unwrap(), PathBuf::from("Song.flac"));
+    }
+
+    #[test]
+    fn sanitizes_separators_inside_fields() {
+        let tags = tags("What? Now: Live", "AC/DC", "Back\\Black", "1980", "1");
+        let path = rendered("{artist}/{album}/{title}.{ext}", &tags, "/in/a.ogg").unwrap();
+        assert_eq!(path, PathBuf::from("AC_DC/Back_Black/What_ Now_ Live.ogg"));
+    }
+
+    #[test]
+    fn rejects_bad_templates() {
+        assert!(parse_template("{title").is_err());
+        assert!(parse_template("{nope}.{ext}").is_err());
+        assert!(parse_template("{track:xx}.{ext}").is_err());
+        assert!(rendered("{artist}/{title}", &tags("Song", "Artist", "", "", ""), "/in/a.mp3").is_err());
+    }
+
+    #[test]
+    fn marks_collisions_ignoring_case_and_skips_the_playing_file() {
+        let root = Path::new("/nonexistent-organizer-root");
+        let sources = vec![
+            (PathBuf::from("/in/1.mp3"), Ok(tags("Song", "Artist", "Album", "", "1"))),
+            (PathBuf::from("/in/2.mp3"), Ok(tags("SONG", "artist", "ALBUM", "", "01"))),
+            (PathBuf::from("/in/3.mp3"), Ok(tags("Other", "Artist", "Album", "", "2"))),
+            (PathBuf::from("/in/4.mp3"), Ok(tags("Playing", "Artist", "Album", "", "3"))),
+            (PathBuf::from("/in/5.mp3"), Err("No tags".to_string())),
+        ];
+        let planned = plan(&sources, "{artist}/{album}/{track:02} {title}.{ext}", root, Some(Path::new("/in/4.mp3"))).unwrap();
+        let statuses: Vec<&MoveStatus> = planned.iter().map(|m| &m.status).collect();
+        assert!(matches!(statuses[0], MoveStatus::Collision(_)));
+        assert!(matches!(statuses[1], MoveStatus::Collision(_)));
+        assert_eq!(*statuses[2], MoveStatus::Move);
+        assert_eq!(planned[2].to, root.join("Artist/Album/02 Other.mp3"));
+        assert_eq!(*statuses[3], MoveStatus::Skipped("Playing now".to_string()));
+        assert_eq!(*statuses[4], MoveStatus::Skipped("No tags".to_string()));
+    }
+}
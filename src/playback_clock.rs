@@ -0,0 +1,37 @@
+// src/playback_clock.rs
+// Relógio de reprodução compartilhado entre a thread de áudio e a UI. A SymphoniaSource
+// soma o tempo de cada pacote conforme ele sai para o rodio, então o valor acompanha o
+// áudio de verdade (pausas, travadas, seeks), não o relógio da parede.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[derive(Default)]
+pub struct PlaybackClock {
+    played_us: AtomicU64, // Áudio tocado desde que a faixa foi carregada; seeks não zeram
+    position_us: AtomicU64, // Posição na faixa do último pacote entregue (relativa ao início da faixa)
+}
+
+impl PlaybackClock {
+    // Nova faixa
+    pub fn reset(&self) {
+        self.played_us.store(0, Ordering::Relaxed);
+        self.position_us.store(0, Ordering::Relaxed);
+    }
+
+    pub fn add_played(&self, amount: Duration) {
+        self.played_us.fetch_add(amount.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn set_position(&self, position: Duration) {
+        self.position_us.store(position.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn position(&self) -> Duration {
+        Duration::from_micros(self.position_us.load(Ordering::Relaxed))
+    }
+
+    pub fn played(&self) -> Duration {
+        Duration::from_micros(self.played_us.load(Ordering::Relaxed))
+    }
+}
@@ -0,0 +1,82 @@
+// src/playback_control.rs
+// Ajustes que a thread de áudio repassa às fontes que já estão no Sink: o laço A-B (lido
+// pela SymphoniaSource a cada pacote), a velocidade e o tom (lidos pelo TimeStretch e pelo
+// Resample a cada janela). A UI muda tudo por AudioCommand; só a thread de áudio escreve aqui.
+//
+// Velocidade e tom saem da combinação das duas fontes: o Resample muda velocidade e altura
+// juntos (como uma fita), o TimeStretch só a velocidade. Para tocar a `speed` com o tom
+// multiplicado por `pitch`:
+//   altura preservada: reamostra por `pitch` e estica por `speed / pitch`
+//   altura livre:      reamostra por `speed × pitch` e estica por `1 / pitch`
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+pub const MIN_SPEED: f32 = 0.5;
+pub const MAX_SPEED: f32 = 3.0;
+pub const MAX_PITCH_SEMITONES: f32 = 12.0;
+
+pub struct PlaybackControl {
+    loop_start_us: AtomicU64,
+    loop_end_us: AtomicU64, // 0 = sem laço
+    speed_bits: AtomicU32,
+    preserve_pitch: AtomicBool,
+    pitch_bits: AtomicU32, // Semitons
+}
+
+impl Default for PlaybackControl {
+    fn default() -> Self {
+        PlaybackControl {
+            loop_start_us: AtomicU64::new(0),
+            loop_end_us: AtomicU64::new(0),
+            speed_bits: AtomicU32::new(1.0f32.to_bits()),
+            preserve_pitch: AtomicBool::new(true),
+            pitch_bits: AtomicU32::new(0.0f32.to_bits()),
+        }
+    }
+}
+
+impl PlaybackControl {
+    // Trecho A-B relativo ao início da faixa; um trecho vazio desliga o laço
+    pub fn set_loop(&self, region: Option<(Duration, Duration)>) {
+        let (start, end) = region.filter(|(start, end)| start < end).unwrap_or_default();
+        // Desliga antes de mover o início, para a fonte nunca ver um A novo com o B antigo
+        self.loop_end_us.store(0, Ordering::Release);
+        self.loop_start_us.store(start.as_micros() as u64, Ordering::Release);
+        self.loop_end_us.store(end.as_micros() as u64, Ordering::Release);
+    }
+
+    pub fn loop_region(&self) -> Option<(Duration, Duration)> {
+        let end = self.loop_end_us.load(Ordering::Acquire);
+        if end == 0 { return None; }
+        Some((Duration::from_micros(self.loop_start_us.load(Ordering::Acquire)), Duration::from_micros(end)))
+    }
+
+    pub fn set_speed(&self, speed: f32) {
+        self.speed_bits.store(speed.clamp(MIN_SPEED, MAX_SPEED).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn set_preserve_pitch(&self, preserve: bool) {
+        self.preserve_pitch.store(preserve, Ordering::Relaxed);
+    }
+
+    pub fn set_pitch_shift(&self, semitones: f32) {
+        self.pitch_bits.store(semitones.clamp(-MAX_PITCH_SEMITONES, MAX_PITCH_SEMITONES).to_bits(), Ordering::Relaxed);
+    }
+
+    // (andamento do TimeStretch, razão do Resample); 1.0 exato desliga cada um
+    fn factors(&self) -> (f32, f32) {
+        let speed = f32::from_bits(self.speed_bits.load(Ordering::Relaxed));
+        let semitones = f32::from_bits(self.pitch_bits.load(Ordering::Relaxed));
+        let pitch = if semitones == 0.0 { 1.0 } else { (semitones / 12.0).exp2() };
+        if self.preserve_pitch.load(Ordering::Relaxed) { (speed / pitch, pitch) } else { (1.0 / pitch, speed * pitch) }
+    }
+
+    pub fn stretch_tempo(&self) -> f32 {
+        self.factors().0
+    }
+
+    pub fn resample_ratio(&self) -> f32 {
+        self.factors().1
+    }
+}
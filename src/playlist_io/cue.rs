@@ -0,0 +1,241 @@
+// src/playlist_io/cue.rs
+// CUE sheets: um único arquivo de áudio (ex.: álbum inteiro em FLAC) dividido em faixas.
+// Cada faixa vira uma entrada "virtual" na playlist com o caminho `<arquivo.cue>/<NN>`,
+// resolvida na hora de tocar para o arquivo de áudio e o trecho entre dois INDEX.
+// As folhas lidas ficam num cache por caminho: o nome e as tags das faixas são pedidos a
+// cada frame, e sem ele cada linha da tabela voltaria ao disco.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use super::{decode_text, resolve_location};
+
+// Os tempos do CUE são mm:ss:ff, com 75 frames por segundo (setores de CD)
+const CUE_FRAMES_PER_SECOND: u64 = 75;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CueSheet {
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    pub tracks: Vec<CueTrack>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueTrack {
+    pub number: u32,
+    pub file: PathBuf,
+    pub title: Option<String>,
+    pub performer: Option<String>, // PERFORMER da faixa ou, na falta dele, o do álbum
+    pub album: Option<String>,
+    pub start: Duration,
+    pub end: Option<Duration>, // None: vai até o fim do arquivo
+}
+
+impl CueTrack {
+    // Duração do trecho; a última faixa de um arquivo depende da duração do arquivo
+    pub fn duration(&self, file_duration: Option<Duration>) -> Option<Duration> {
+        self.end.or(file_duration).map(|end| end.saturating_sub(self.start))
+    }
+}
+
+pub fn parse(text: &str, base_dir: &Path) -> CueSheet {
+    let mut sheet = CueSheet::default();
+    let mut current_file: Option<PathBuf> = None;
+    // (faixa, INDEX 00, INDEX 01)
+    let mut pending: Vec<(CueTrack, Option<Duration>, Option<Duration>)> = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => current_file = Some(resolve_location(&unquote(rest), base_dir)),
+            "TRACK" => {
+                let Some(file) = current_file.clone() else { continue; };
+                let number = rest.split_whitespace().next().and_then(|n| n.parse().ok()).unwrap_or(pending.len() as u32 + 1);
+                let track = CueTrack { number, file, title: None, performer: None, album: None, start: Duration::ZERO, end: None };
+                pending.push((track, None, None));
+            }
+            "TITLE" | "PERFORMER" => {
+                let value = Some(unquote(rest)).filter(|v| !v.is_empty());
+                let is_title = command.eq_ignore_ascii_case("TITLE");
+                // Antes do primeiro TRACK os campos são do álbum
+                match (pending.last_mut(), is_title) {
+                    (Some((track, _, _)), true) => track.title = value,
+                    (Some((track, _, _)), false) => track.performer = value,
+                    (None, true) => sheet.title = value,
+                    (None, false) => sheet.performer = value,
+                }
+            }
+            "INDEX" => {
+                let Some((_, pregap, start)) = pending.last_mut() else { continue; };
+                let mut parts = rest.split_whitespace();
+                let (Some(number), Some(time)) = (parts.next(), parts.next().and_then(parse_time)) else { continue; };
+                match number.parse::<u32>() {
+                    Ok(0) => *pregap = Some(time),
+                    Ok(1) => *start = Some(time),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    sheet.tracks = pending.into_iter()
+        .map(|(mut track, pregap, start)| {
+            track.start = start.or(pregap).unwrap_or(Duration::ZERO);
+            track.performer = track.performer.or_else(|| sheet.performer.clone());
+            track.album = sheet.title.clone();
+            track
+        })
+        .collect();
+    // Cada faixa termina onde começa a próxima do mesmo arquivo
+    for i in 1..sheet.tracks.len() {
+        if sheet.tracks[i].file == sheet.tracks[i - 1].file {
+            sheet.tracks[i - 1].end = Some(sheet.tracks[i].start);
+        }
+    }
+    sheet
+}
+
+// Lê do disco e atualiza o cache (adicionar a pasta de novo recarrega um .cue editado ou
+// que voltou a existir)
+pub fn read(cue_path: &Path) -> Result<CueSheet, String> {
+    let result = read_from_disk(cue_path).map(Arc::new);
+    sheet_cache().lock().unwrap().insert(cue_path.to_path_buf(), result.clone());
+    result.map(|sheet| (*sheet).clone())
+}
+
+// Folha já lida, ou lida agora. Erros também ficam no cache: os nomes das faixas são
+// consultados a cada quadro, e um .cue sumido não pode virar uma leitura de disco por linha.
+fn cached(cue_path: &Path) -> Result<Arc<CueSheet>, String> {
+    if let Some(result) = sheet_cache().lock().unwrap().get(cue_path) { return result.clone(); }
+    let result = read_from_disk(cue_path).map(Arc::new);
+    sheet_cache().lock().unwrap().insert(cue_path.to_path_buf(), result.clone());
+    result
+}
+
+type SheetCache = HashMap<PathBuf, Result<Arc<CueSheet>, String>>;
+
+fn sheet_cache() -> &'static Mutex<SheetCache> {
+    static SHEETS: OnceLock<Mutex<SheetCache>> = OnceLock::new();
+    SHEETS.get_or_init(Default::default)
+}
+
+fn read_from_disk(cue_path: &Path) -> Result<CueSheet, String> {
+    let bytes = std::fs::read(cue_path).map_err(|e| format!("Failed to read CUE sheet: {}", e))?;
+    let base_dir = cue_path.parent().unwrap_or(Path::new("."));
+    let mut sheet = parse(&decode_text(&bytes, false), base_dir);
+    // CUEs gerados ao ripar citam o .wav original mesmo depois da conversão para FLAC
+    for track in &mut sheet.tracks {
+        if !track.file.exists() {
+            if let Some(found) = find_converted_file(&track.file) { track.file = found; }
+        }
+    }
+    Ok(sheet)
+}
+
+fn find_converted_file(file: &Path) -> Option<PathBuf> {
+    crate::files::SUPPORTED_EXTENSIONS.iter()
+        .map(|ext| file.with_extension(ext))
+        .find(|candidate| candidate.exists())
+}
+
+// --- Caminhos virtuais das faixas ---
+pub fn track_path(cue_path: &Path, number: u32) -> PathBuf {
+    cue_path.join(format!("{:02}", number))
+}
+
+// "<álbum.cue>/03" -> (álbum.cue, 3); o .cue precisa existir (consultado pelo cache)
+pub fn split_track_path(path: &Path) -> Option<(&Path, u32)> {
+    let cue_path = path.parent()?;
+    let is_cue = cue_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("cue"));
+    if !is_cue { return None; }
+    let number = path.file_name()?.to_str()?.parse().ok()?;
+    cached(cue_path).ok()?;
+    Some((cue_path, number))
+}
+
+// None se o caminho não for uma faixa de CUE sheet
+pub fn read_track(path: &Path) -> Option<Result<CueTrack, String>> {
+    let (cue_path, number) = split_track_path(path)?;
+    Some(cached(cue_path).and_then(|sheet| {
+        sheet.tracks.iter()
+            .find(|track| track.number == number)
+            .cloned()
+            .ok_or_else(|| format!("Track {} not found in CUE sheet {}", number, cue_path.display()))
+    }))
+}
+
+fn parse_time(text: &str) -> Option<Duration> {
+    let mut parts = text.split(':').map(|p| p.parse::<u64>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    let total_frames = (minutes * 60 + seconds) * CUE_FRAMES_PER_SECOND + frames;
+    Some(Duration::from_nanos(total_frames * 1_000_000_000 / CUE_FRAMES_PER_SECOND))
+}
+
+// `"Nome do arquivo.flac" WAVE` -> Nome do arquivo.flac (aspas são opcionais)
+fn unquote(text: &str) -> String {
+    match text.strip_prefix('"') {
+        Some(rest) => rest.split('"').next().unwrap_or_default().to_string(),
+        None => {
+            // FILE sem aspas: o último token é o tipo (WAVE, MP3...)
+            let mut tokens: Vec<&str> = text.split_whitespace().collect();
+            if tokens.len() > 1 && tokens.last().is_some_and(|t| t.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())) { tokens.pop(); }
+            tokens.join(" ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("fixtures/album.cue");
+
+    #[test]
+    fn parses_tracks_with_offsets_and_performers() {
+        let sheet = parse(FIXTURE, Path::new("/music/Album"));
+        assert_eq!(sheet.title.as_deref(), Some("Live Album"));
+        assert_eq!(sheet.tracks.len(), 4);
+
+        let first = &sheet.tracks[0];
+        assert_eq!(first.number, 1);
+        assert_eq!(first.file, PathBuf::from("/music/Album/Live Album.flac"));
+        assert_eq!(first.title.as_deref(), Some("Opening"));
+        assert_eq!(first.performer.as_deref(), Some("The Band"));
+        assert_eq!(first.album.as_deref(), Some("Live Album"));
+        assert_eq!(first.start, Duration::ZERO);
+        assert_eq!(first.end, Some(Duration::from_millis(185_400)));
+
+        // PERFORMER próprio da faixa; INDEX 00 (pregap) não muda o início
+        let second = &sheet.tracks[1];
+        assert_eq!(second.performer.as_deref(), Some("Guest Singer"));
+        assert_eq!(second.start, Duration::from_millis(185_400));
+        assert_eq!(second.end, Some(Duration::from_secs(402)));
+
+        // A última faixa do primeiro arquivo vai até o fim dele
+        assert_eq!(sheet.tracks[2].end, None);
+        assert_eq!(sheet.tracks[2].duration(Some(Duration::from_secs(500))), Some(Duration::from_secs(98)));
+
+        let encore = &sheet.tracks[3];
+        assert_eq!(encore.file, PathBuf::from("/music/Album/Encore.wav"));
+        assert_eq!(encore.start, Duration::ZERO);
+        assert_eq!(encore.end, None);
+    }
+
+    #[test]
+    fn parses_cd_frame_times() {
+        assert_eq!(parse_time("01:02:15"), Some(Duration::from_millis(62_200)));
+        assert_eq!(parse_time("bad"), None);
+    }
+
+    #[test]
+    fn track_paths_are_children_of_the_cue_file() {
+        assert_eq!(track_path(Path::new("/music/a.cue"), 3), PathBuf::from("/music/a.cue/03"));
+        // O .cue precisa existir no disco
+        assert_eq!(split_track_path(Path::new("/nonexistent/a.cue/03")), None);
+    }
+}
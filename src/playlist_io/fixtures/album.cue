@@ -0,0 +1,20 @@
+REM GENRE Rock
+REM DATE 2004
+PERFORMER "The Band"
+TITLE "Live Album"
+FILE "Live Album.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "Opening"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Duet"
+    PERFORMER "Guest Singer"
+    INDEX 00 03:03:00
+    INDEX 01 03:05:30
+  TRACK 03 AUDIO
+    TITLE "Closing"
+    INDEX 01 06:42:00
+FILE Encore.wav WAVE
+  TRACK 04 AUDIO
+    TITLE "Encore"
+    INDEX 01 00:00:00
@@ -0,0 +1,119 @@
+// src/playlist_io/m3u.rs
+// M3U / M3U8 estendido (#EXTM3U, #EXTINF:<segundos>,<título>).
+
+use std::path::Path;
+use std::time::Duration;
+
+use super::{relative_location, resolve_entry, PlaylistEntry};
+
+pub fn parse(text: &str, base_dir: &Path) -> Vec<PlaylistEntry> {
+    let mut entries = Vec::new();
+    let mut pending_info: Option<(Option<Duration>, Option<String>)> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            pending_info = Some(parse_extinf(info));
+            continue;
+        }
+        if line.starts_with('#') { continue; }
+        let (duration, title) = pending_info.take().unwrap_or((None, None));
+        // URLs de rede (http, etc.) não fazem sentido para um player de arquivos locais
+        match resolve_entry(line, base_dir) {
+            Some(path) => entries.push(PlaylistEntry { path, title, duration }),
+            None => eprintln!("M3U: Skipping non-local entry: {}", line),
+        }
+    }
+    entries
+}
+
+// "<duração>[ atributos],<título>"; duração -1 significa desconhecida
+fn parse_extinf(info: &str) -> (Option<Duration>, Option<String>) {
+    let (head, title) = match info.split_once(',') {
+        Some((head, title)) => (head, Some(title.trim().to_string()).filter(|t| !t.is_empty())),
+        None => (info, None),
+    };
+    let seconds = head.split_whitespace().next().and_then(|s| s.parse::<f64>().ok());
+    // Negativos, NaN, inf e valores enormes (arquivo corrompido) ficam sem duração
+    let duration = seconds.and_then(|s| Duration::try_from_secs_f64(s).ok());
+    (duration, title)
+}
+
+pub fn write(entries: &[PlaylistEntry], base_dir: &Path) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for entry in entries {
+        let seconds = entry.duration.map_or(-1, |d| d.as_secs() as i64);
+        let title = entry.title.clone()
+            .unwrap_or_else(|| entry.path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default());
+        out.push_str(&format!("#EXTINF:{},{}\n", seconds, title));
+        out.push_str(&relative_location(&entry.path, base_dir));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const FIXTURE: &str = include_str!("fixtures/example.m3u8");
+
+    #[test]
+    fn parses_fixture() {
+        let entries = parse(FIXTURE, Path::new("/music/lists"));
+        // A entrada http:// é ignorada
+        assert_eq!(entries.len(), 4);
+
+        assert_eq!(entries[0].path, PathBuf::from("/music/lists/Artist/01 First Song.flac"));
+        assert_eq!(entries[0].title.as_deref(), Some("Artist - First Song"));
+        assert_eq!(entries[0].duration, Some(Duration::from_secs(215)));
+
+        assert_eq!(entries[1].path, PathBuf::from("/music/Ação & Cia/02 Canção.mp3"));
+        assert_eq!(entries[1].title.as_deref(), Some("Canção"));
+        assert_eq!(entries[1].duration, None);
+
+        // Sem #EXTINF próprio: o da entrada anterior não vaza para esta
+        assert_eq!(entries[2].path, PathBuf::from("/music/other/03.ogg"));
+        assert_eq!(entries[2].title, None);
+        assert_eq!(entries[2].duration, None);
+
+        assert_eq!(entries[3].path, PathBuf::from("/music/broken.mp3"));
+        assert_eq!(entries[3].title.as_deref(), Some("Broken length"));
+        assert_eq!(entries[3].duration, None);
+    }
+
+    #[test]
+    fn parses_extinf_durations_and_attributes() {
+        assert_eq!(parse_extinf("12.5 tvg-id=\"x\",Title"), (Some(Duration::from_millis(12_500)), Some("Title".to_string())));
+        assert_eq!(parse_extinf("-1,Title"), (None, Some("Title".to_string())));
+        assert_eq!(parse_extinf("30,  "), (Some(Duration::from_secs(30)), None));
+        assert_eq!(parse_extinf("30"), (Some(Duration::from_secs(30)), None));
+        assert_eq!(parse_extinf("abc,Title"), (None, Some("Title".to_string())));
+    }
+
+    #[test]
+    fn ignores_non_finite_and_huge_durations() {
+        for head in ["inf", "-inf", "NaN", "1e30"] {
+            assert_eq!(parse_extinf(&format!("{},Title", head)).0, None, "{}", head);
+        }
+    }
+
+    #[test]
+    fn round_trips_durations_and_locations() {
+        let base = Path::new("/music/lists");
+        let entries = parse(FIXTURE, base);
+        let reparsed = parse(&write(&entries, base), base);
+        let key = |entries: &[PlaylistEntry]| entries.iter().map(|e| (e.path.clone(), e.duration)).collect::<Vec<_>>();
+        assert_eq!(key(&entries), key(&reparsed));
+        assert_eq!(reparsed[0].title.as_deref(), Some("Artist - First Song"));
+    }
+
+    #[test]
+    fn writes_unknown_duration_and_file_stem_title() {
+        let entries = vec![PlaylistEntry { path: PathBuf::from("/music/lists/a.mp3"), title: None, duration: None }];
+        let text = write(&entries, Path::new("/music/lists"));
+        assert_eq!(text, "#EXTM3U\n#EXTINF:-1,a\na.mp3\n");
+    }
+}
@@ -0,0 +1,141 @@
+// src/playlist_io/mod.rs
+// Leitura e escrita de arquivos de playlist para trocar listas com outros players.
+
+pub mod cue;
+pub mod m3u;
+pub mod pls;
+pub mod xspf;
+
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+// --- Entrada lida/escrita de um arquivo de playlist ---
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistEntry {
+    pub path: PathBuf,
+    pub title: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+// Formatos suportados, escolhidos pela extensão do arquivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    M3u,
+    M3u8,
+    Pls,
+    Xspf,
+}
+
+impl PlaylistFormat {
+    pub const EXTENSIONS: [&'static str; 4] = ["m3u", "m3u8", "pls", "xspf"];
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "m3u" => Some(PlaylistFormat::M3u),
+            "m3u8" => Some(PlaylistFormat::M3u8),
+            "pls" => Some(PlaylistFormat::Pls),
+            "xspf" => Some(PlaylistFormat::Xspf),
+            _ => None,
+        }
+    }
+}
+
+pub fn read_playlist(path: &Path) -> Result<Vec<PlaylistEntry>, String> {
+    let format = PlaylistFormat::from_path(path).ok_or_else(|| format!("Unsupported playlist format: {}", path.display()))?;
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read playlist: {}", e))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    match format {
+        PlaylistFormat::M3u | PlaylistFormat::M3u8 => Ok(m3u::parse(&decode_text(&bytes, format == PlaylistFormat::M3u8), base_dir)),
+        PlaylistFormat::Pls => Ok(pls::parse(&decode_text(&bytes, false), base_dir)),
+        PlaylistFormat::Xspf => xspf::parse(&decode_text(&bytes, true), base_dir),
+    }
+}
+
+pub fn write_playlist(path: &Path, entries: &[PlaylistEntry]) -> Result<(), String> {
+    let format = PlaylistFormat::from_path(path).ok_or_else(|| format!("Unsupported playlist format: {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let contents = match format {
+        PlaylistFormat::M3u | PlaylistFormat::M3u8 => m3u::write(entries, base_dir),
+        PlaylistFormat::Pls => pls::write(entries, base_dir),
+        PlaylistFormat::Xspf => xspf::write(entries, base_dir),
+    };
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write playlist: {}", e))
+}
+
+// Texto do arquivo: UTF-8 (sem BOM). Arquivos .m3u antigos que não são UTF-8 válido
+// são lidos como Latin-1, a codificação usada pelo formato original.
+pub fn decode_text(bytes: &[u8], utf8_only: bool) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) if utf8_only => String::from_utf8_lossy(bytes).into_owned(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+// Resolve um caminho da playlist: relativos são relativos à pasta do arquivo de playlist.
+// Separadores "\" de playlists feitas no Windows são convertidos fora do Windows.
+pub fn resolve_location(location: &str, base_dir: &Path) -> PathBuf {
+    let location = if cfg!(windows) { location.to_string() } else { location.replace('\\', "/") };
+    let path = PathBuf::from(location);
+    if path.is_absolute() { path } else { normalize(&base_dir.join(path)) }
+}
+
+// Localização de uma entrada: URI file://, caminho absoluto ou relativo.
+// Retorna None para URLs remotas (http, etc.), que o player não toca.
+pub fn resolve_entry(location: &str, base_dir: &Path) -> Option<PathBuf> {
+    if let Some(path) = file_uri_to_path(location) { return Some(path); }
+    if location.contains("://") { return None; }
+    Some(resolve_location(location, base_dir))
+}
+
+// --- URIs file:// ---
+// Caracteres mantidos sem codificação em caminhos (RFC 3986 "unreserved" e o separador "/")
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'/').remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+// "file:///music/a%20b.mp3" ou "file://localhost/music/..." -> /music/a b.mp3
+pub fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://").or_else(|| uri.strip_prefix("FILE://"))?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    // No Windows a URI é file:///C:/..., sem a barra inicial no caminho
+    let rest = if cfg!(windows) { rest.trim_start_matches('/') } else { rest };
+    Some(PathBuf::from(percent_decode(rest)))
+}
+
+pub fn path_to_file_uri(path: &Path) -> String {
+    let text = path.to_string_lossy().replace('\\', "/");
+    let prefix = if text.starts_with('/') { "file://" } else { "file:///" };
+    format!("{}{}", prefix, percent_encode(&text))
+}
+
+pub fn percent_encode(text: &str) -> String {
+    utf8_percent_encode(text, PATH_SEGMENT).to_string()
+}
+
+pub fn percent_decode(text: &str) -> String {
+    percent_decode_str(text).decode_utf8_lossy().into_owned()
+}
+
+// Caminho como deve ser gravado: relativo à pasta da playlist quando a faixa está dentro dela
+pub fn relative_location(path: &Path, base_dir: &Path) -> String {
+    match path.strip_prefix(base_dir) {
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+// Remove "." e ".." sem acessar o disco
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => { normalized.pop(); }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
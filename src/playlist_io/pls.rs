@@ -0,0 +1,104 @@
+// src/playlist_io/pls.rs
+// PLS ([playlist], FileN=, TitleN=, LengthN=, NumberOfEntries=, Version=2).
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use super::{relative_location, resolve_entry, PlaylistEntry};
+
+#[derive(Default)]
+struct PlsFields {
+    file: Option<String>,
+    title: Option<String>,
+    length: Option<i64>,
+}
+
+pub fn parse(text: &str, base_dir: &Path) -> Vec<PlaylistEntry> {
+    // As chaves são numeradas e podem aparecer fora de ordem
+    let mut fields: BTreeMap<usize, PlsFields> = BTreeMap::new();
+    for line in text.lines() {
+        let line = line.trim();
+        let Some((key, value)) = line.split_once('=') else { continue; };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        let (name, number) = key.split_at(key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len()));
+        let Ok(number) = number.parse::<usize>() else { continue; };
+        let entry = fields.entry(number).or_default();
+        match name {
+            "file" => entry.file = Some(value.to_string()),
+            "title" => entry.title = Some(value.to_string()).filter(|t| !t.is_empty()),
+            "length" => entry.length = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    fields.into_values()
+        .filter_map(|fields| {
+            let location = fields.file?;
+            let path = resolve_entry(&location, base_dir).or_else(|| {
+                eprintln!("PLS: Skipping non-local entry: {}", location);
+                None
+            })?;
+            let duration = fields.length.filter(|secs| *secs >= 0).map(|secs| Duration::from_secs(secs as u64));
+            Some(PlaylistEntry { path, title: fields.title, duration })
+        })
+        .collect()
+}
+
+pub fn write(entries: &[PlaylistEntry], base_dir: &Path) -> String {
+    let mut out = String::from("[playlist]\n");
+    for (i, entry) in entries.iter().enumerate() {
+        let number = i + 1;
+        out.push_str(&format!("File{}={}\n", number, relative_location(&entry.path, base_dir)));
+        if let Some(title) = &entry.title {
+            out.push_str(&format!("Title{}={}\n", number, title));
+        }
+        let seconds = entry.duration.map_or(-1, |d| d.as_secs() as i64);
+        out.push_str(&format!("Length{}={}\n", number, seconds));
+    }
+    out.push_str(&format!("NumberOfEntries={}\nVersion=2\n", entries.len()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const FIXTURE: &str = include_str!("fixtures/example.pls");
+
+    #[test]
+    fn parses_fixture_in_numeric_order() {
+        let entries = parse(FIXTURE, Path::new("/music/lists"));
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].path, PathBuf::from("/music/lists/Artist/01 First Song.flac"));
+        assert_eq!(entries[0].title.as_deref(), Some("Artist - First Song"));
+        assert_eq!(entries[0].duration, Some(Duration::from_secs(215)));
+
+        // file:// com percent-encoding e caracteres não ASCII
+        assert_eq!(entries[1].path, PathBuf::from("/music/Ação & Cia/02 Canção.mp3"));
+        assert_eq!(entries[1].duration, None);
+
+        assert_eq!(entries[2].path, PathBuf::from("/music/other/03.ogg"));
+        assert_eq!(entries[2].title, None);
+    }
+
+    #[test]
+    fn round_trips_titles_durations_and_locations() {
+        let base = Path::new("/music/lists");
+        let entries = parse(FIXTURE, base);
+        let reparsed = parse(&write(&entries, base), base);
+        assert_eq!(entries, reparsed);
+    }
+
+    #[test]
+    fn writes_relative_locations_inside_playlist_folder() {
+        let entries = vec![PlaylistEntry { path: PathBuf::from("/music/lists/a.mp3"), title: None, duration: None }];
+        let text = write(&entries, Path::new("/music/lists"));
+        assert!(text.contains("File1=a.mp3\n"));
+        assert!(text.contains("Length1=-1\n"));
+        assert!(text.contains("NumberOfEntries=1\n"));
+    }
+}
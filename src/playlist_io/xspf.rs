@@ -0,0 +1,147 @@
+// src/playlist_io/xspf.rs
+// XSPF ("XML Shareable Playlist Format"): <playlist><trackList><track>...</track></trackList></playlist>.
+// <location> é uma URI (file:// ou relativa, com percent-encoding); <duration> está em milissegundos.
+
+use std::path::Path;
+use std::time::Duration;
+
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use super::{path_to_file_uri, percent_decode, percent_encode, resolve_entry, PlaylistEntry};
+
+#[derive(Default)]
+struct TrackFields {
+    location: Option<String>,
+    title: Option<String>,
+    duration_ms: Option<u64>,
+}
+
+pub fn parse(text: &str, base_dir: &Path) -> Result<Vec<PlaylistEntry>, String> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+
+    let mut entries = Vec::new();
+    let mut current_track: Option<TrackFields> = None;
+    let mut current_element: Option<String> = None;
+
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid XSPF at position {}: {}", reader.buffer_position(), e))?;
+        match event {
+            Event::Start(element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+                if name == "track" { current_track = Some(TrackFields::default()); }
+                current_element = Some(name);
+            }
+            Event::Text(content) => {
+                let value = content.unescape().map_err(|e| format!("Invalid XSPF text: {}", e))?.into_owned();
+                store_field(&mut current_track, current_element.as_deref(), value);
+            }
+            Event::CData(content) => {
+                let value = String::from_utf8_lossy(&content.into_inner()).into_owned();
+                store_field(&mut current_track, current_element.as_deref(), value);
+            }
+            Event::End(element) => {
+                if element.local_name().as_ref() == b"track" {
+                    if let Some(track) = current_track.take() {
+                        if let Some(entry) = track_to_entry(track, base_dir) { entries.push(entry); }
+                    }
+                }
+                current_element = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+fn store_field(track: &mut Option<TrackFields>, element: Option<&str>, value: String) {
+    let Some(track) = track.as_mut() else { return; };
+    match element {
+        // Uma faixa pode ter várias <location>; a primeira é a preferida
+        Some("location") if track.location.is_none() => track.location = Some(value.trim().to_string()),
+        Some("title") => track.title = Some(value.trim().to_string()).filter(|t| !t.is_empty()),
+        Some("duration") => track.duration_ms = value.trim().parse().ok(),
+        _ => {}
+    }
+}
+
+fn track_to_entry(track: TrackFields, base_dir: &Path) -> Option<PlaylistEntry> {
+    let location = track.location?;
+    // Localizações relativas também são URIs, então precisam ser decodificadas
+    let path = if location.contains("://") { resolve_entry(&location, base_dir) } else { resolve_entry(&percent_decode(&location), base_dir) };
+    let Some(path) = path else {
+        eprintln!("XSPF: Skipping non-local entry: {}", location);
+        return None;
+    };
+    Some(PlaylistEntry { path, title: track.title, duration: track.duration_ms.map(Duration::from_millis) })
+}
+
+pub fn write(entries: &[PlaylistEntry], base_dir: &Path) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n  <trackList>\n");
+    for entry in entries {
+        let location = match entry.path.strip_prefix(base_dir) {
+            Ok(relative) => percent_encode(&relative.to_string_lossy().replace('\\', "/")),
+            Err(_) => path_to_file_uri(&entry.path),
+        };
+        out.push_str("    <track>\n");
+        out.push_str(&format!("      <location>{}</location>\n", escape(location.as_str())));
+        if let Some(title) = &entry.title {
+            out.push_str(&format!("      <title>{}</title>\n", escape(title.as_str())));
+        }
+        if let Some(duration) = entry.duration {
+            out.push_str(&format!("      <duration>{}</duration>\n", duration.as_millis()));
+        }
+        out.push_str("    </track>\n");
+    }
+    out.push_str("  </trackList>\n</playlist>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const FIXTURE: &str = include_str!("fixtures/example.xspf");
+
+    #[test]
+    fn parses_fixture() {
+        let entries = parse(FIXTURE, Path::new("/music/lists")).unwrap();
+        // A faixa http:// é ignorada
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].path, PathBuf::from("/music/Ação & Cia/02 Canção.mp3"));
+        assert_eq!(entries[0].title.as_deref(), Some("Canção <ao vivo> & mais"));
+        assert_eq!(entries[0].duration, Some(Duration::from_millis(183_500)));
+
+        assert_eq!(entries[1].path, PathBuf::from("/music/lists/Artist/01 First Song.flac"));
+        assert_eq!(entries[1].title.as_deref(), Some("First Song"));
+        assert_eq!(entries[1].duration, None);
+
+        assert_eq!(entries[2].path, PathBuf::from("/music/other/03.ogg"));
+        assert_eq!(entries[2].title, None);
+    }
+
+    #[test]
+    fn round_trips_titles_durations_and_locations() {
+        let base = Path::new("/music/lists");
+        let entries = parse(FIXTURE, base).unwrap();
+        let reparsed = parse(&write(&entries, base), base).unwrap();
+        assert_eq!(entries, reparsed);
+    }
+
+    #[test]
+    fn writes_percent_encoded_file_uris_outside_playlist_folder() {
+        let entries = vec![PlaylistEntry { path: PathBuf::from("/music/Ação & Cia/a b.mp3"), title: None, duration: None }];
+        let text = write(&entries, Path::new("/music/lists"));
+        assert!(text.contains("<location>file:///music/A%C3%A7%C3%A3o%20%26%20Cia/a%20b.mp3</location>"));
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        assert!(parse("<playlist><trackList><track></playlist>", Path::new("/")).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+// src/resample.rs
+// Reamostragem com razão variável: consome `ratio` frames da fonte por frame de saída, o que
+// muda velocidade e altura juntas (velocidade sem preservar o tom e mudança de tom).
+// Interpolação cúbica (Hermite) entre os frames vizinhos. Com razão 1.0 só repassa as amostras.
+// Com razão acima de 1 (acelerando) a fonte passa antes por um passa-baixa: o que fica acima da
+// nova frequência de Nyquist voltaria rebatido como ruído (aliasing).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::playback_control::PlaybackControl;
+
+// A razão é consultada a cada tantos frames de saída
+const RATIO_CHECK_FRAMES: usize = 256;
+// Corte do passa-baixa como fração da nova frequência de Nyquist (folga para a inclinação)
+const ANTI_ALIAS_CUTOFF: f64 = 0.9;
+// Q das duas biquads de um Butterworth de 4ª ordem
+const BUTTERWORTH_Q: [f64; 2] = [0.541_196_1, 1.306_563];
+
+// --- Passa-baixa ---
+// Biquad passa-baixa (RBJ), forma direta II transposta
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    // `cutoff` em ciclos por amostra (0.5 = Nyquist)
+    fn low_pass(cutoff: f64, q: f64) -> Self {
+        let w0 = 2.0 * std::f64::consts::PI * cutoff;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        let b0 = (1.0 - cos) / 2.0 / a0;
+        Biquad { b0: b0 as f32, b1: (2.0 * b0) as f32, b2: b0 as f32, a1: (-2.0 * cos / a0) as f32, a2: ((1.0 - alpha) / a0) as f32 }
+    }
+
+    #[inline]
+    fn process(&self, x: f32, state: &mut [f32; 2]) -> f32 {
+        let y = self.b0 * x + state[0];
+        state[0] = self.b1 * x - self.a1 * y + state[1];
+        state[1] = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+// Butterworth de 4ª ordem (duas biquads) com estado por canal; desligado com razão até 1
+struct AntiAlias {
+    stages: [Biquad; 2],
+    state: Vec<[[f32; 2]; 2]>,
+    ratio: f64,
+}
+
+impl AntiAlias {
+    fn new(channels: usize) -> Self {
+        AntiAlias { stages: [Biquad::default(); 2], state: vec![[[0.0; 2]; 2]; channels], ratio: 1.0 }
+    }
+
+    fn set_ratio(&mut self, ratio: f64) {
+        if ratio == self.ratio { return; }
+        // Ao ligar, o estado é de quando o filtro estava parado
+        if self.ratio <= 1.0 { self.state.iter_mut().for_each(|state| *state = [[0.0; 2]; 2]); }
+        self.ratio = ratio;
+        if ratio > 1.0 {
+            let cutoff = 0.5 * ANTI_ALIAS_CUTOFF / ratio;
+            self.stages = BUTTERWORTH_Q.map(|q| Biquad::low_pass(cutoff, q));
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        if self.ratio <= 1.0 { return sample; }
+        let state = &mut self.state[channel];
+        let sample = self.stages[0].process(sample, &mut state[0]);
+        self.stages[1].process(sample, &mut state[1])
+    }
+}
+
+pub struct Resample<S> {
+    inner: S,
+    control: Arc<PlaybackControl>,
+    channels: usize,
+    active: bool,
+    ratio: f64,
+    anti_alias: AntiAlias,
+    // Quatro frames seguidos da fonte (intercalados); a saída fica entre o segundo e o terceiro
+    frames: Vec<f32>,
+    frac: f64,
+    output: Vec<f32>,
+    output_pos: usize,
+    frame_pos: usize, // Canal da próxima amostra no modo direto (só troca de modo entre frames)
+    until_check: usize,
+}
+
+impl<S: Source<Item = f32>> Resample<S> {
+    pub fn new(inner: S, control: Arc<PlaybackControl>) -> Self {
+        let channels = inner.channels().max(1) as usize;
+        Resample {
+            inner, control, channels, active: false, ratio: 1.0, anti_alias: AntiAlias::new(channels), frames: vec![0.0; 4 * channels], frac: 0.0,
+            output: Vec::new(), output_pos: 0, frame_pos: 0, until_check: 0,
+        }
+    }
+
+    // Descarta o frame mais antigo e lê o próximo; false se a fonte acabou
+    fn advance(&mut self) -> bool {
+        self.frames.copy_within(self.channels.., 0);
+        let last = 3 * self.channels;
+        for c in 0..self.channels {
+            match self.inner.next() {
+                Some(sample) => self.frames[last + c] = self.anti_alias.process(c, sample),
+                None => return false,
+            }
+        }
+        true
+    }
+
+    // O primeiro frame de saída é exatamente o próximo da fonte, sem salto
+    fn start(&mut self) -> bool {
+        self.anti_alias.set_ratio(self.ratio);
+        for _ in 0..3 {
+            if !self.advance() { return false; }
+        }
+        self.frames.copy_within(self.channels..2 * self.channels, 0);
+        self.frac = 0.0;
+        self.active = true;
+        true
+    }
+
+    // Volta ao modo direto repassando os frames já lidos (perde no máximo a fração de um frame)
+    fn finish(&mut self) {
+        let from = if self.frac == 0.0 { 1 } else { 2 };
+        self.output.clear();
+        self.output.extend_from_slice(&self.frames[from * self.channels..]);
+        self.output_pos = 0;
+        self.active = false;
+        self.frame_pos = 0;
+        self.until_check = RATIO_CHECK_FRAMES;
+        self.anti_alias.set_ratio(1.0);
+    }
+
+    // Próximo frame interpolado em `output`; false se a fonte acabou
+    fn resample_frame(&mut self) -> bool {
+        if self.until_check == 0 {
+            self.until_check = RATIO_CHECK_FRAMES;
+            self.ratio = self.control.resample_ratio() as f64;
+            if self.ratio == 1.0 {
+                self.finish();
+                return true;
+            }
+            self.anti_alias.set_ratio(self.ratio);
+        }
+        self.until_check -= 1;
+        let t = self.frac as f32;
+        let channels = self.channels;
+        self.output.clear();
+        self.output_pos = 0;
+        for c in 0..channels {
+            let [y0, y1, y2, y3] = std::array::from_fn(|i| self.frames[i * channels + c]);
+            let a = -0.5 * y0 + 1.5 * y1 - 1.5 * y2 + 0.5 * y3;
+            let b = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+            let c1 = 0.5 * (y2 - y0);
+            self.output.push(((a * t + b) * t + c1) * t + y1);
+        }
+        self.frac += self.ratio;
+        while self.frac >= 1.0 {
+            if !self.advance() { return false; }
+            self.frac -= 1.0;
+        }
+        true
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Resample<S> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        loop {
+            if self.output_pos < self.output.len() {
+                let sample = self.output[self.output_pos];
+                self.output_pos += 1;
+                return Some(sample);
+            }
+            if self.active {
+                // O frame em `output` já calculado ainda sai mesmo se a fonte acabou ao avançar
+                if !self.resample_frame() { self.active = false; self.frame_pos = 0; }
+                continue;
+            }
+            if self.frame_pos == 0 {
+                if self.until_check == 0 {
+                    self.until_check = RATIO_CHECK_FRAMES;
+                    self.ratio = self.control.resample_ratio() as f64;
+                    if self.ratio != 1.0 {
+                        if !self.start() { return None; }
+                        self.until_check = RATIO_CHECK_FRAMES;
+                        continue;
+                    }
+                }
+                self.until_check -= 1;
+            }
+            let sample = self.inner.next()?;
+            self.frame_pos = (self.frame_pos + 1) % self.channels;
+            return Some(sample);
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Resample<S> {
+    // Reamostrando, os blocos não coincidem com os da fonte; o Sink usa então um tamanho fixo
+    #[inline] fn current_frame_len(&self) -> Option<usize> {
+        if self.active || self.output_pos < self.output.len() { None } else { self.inner.current_frame_len() }
+    }
+    #[inline] fn channels(&self) -> u16 { self.inner.channels() }
+    #[inline] fn sample_rate(&self) -> u32 { self.inner.sample_rate() }
+    #[inline] fn total_duration(&self) -> Option<Duration> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    const RATE: u32 = 48_000;
+
+    fn sine(freq: f32, seconds: f32) -> Vec<f32> {
+        (0..(RATE as f32 * seconds) as usize).map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / RATE as f32).sin()).collect()
+    }
+
+    // Velocidade sem preservar o tom: o Resample faz tudo, com razão = velocidade
+    fn resampled(samples: Vec<f32>, channels: u16, speed: f32) -> Vec<f32> {
+        let control = Arc::new(PlaybackControl::default());
+        control.set_preserve_pitch(false);
+        control.set_speed(speed);
+        Resample::new(SamplesBuffer::new(channels, RATE, samples), control).collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn ratio_one_passes_samples_through() {
+        let input: Vec<f32> = (0..10_000).map(|i| (i as f32 * 0.91).sin() * 0.5).collect();
+        assert_eq!(resampled(input.clone(), 2, 1.0), input);
+    }
+
+    #[test]
+    fn output_length_follows_the_ratio() {
+        for speed in [0.5, 1.5, 3.0] {
+            let input = sine(1000.0, 1.0);
+            let expected = input.len() as f32 / speed;
+            let output = resampled(input, 1, speed);
+            assert!((output.len() as f32 - expected).abs() < 8.0, "speed {}: {} samples, expected about {}", speed, output.len(), expected);
+        }
+    }
+
+    #[test]
+    fn stereo_channels_stay_apart() {
+        let input: Vec<f32> = sine(500.0, 0.5).into_iter().flat_map(|s| [s, 0.25]).collect();
+        let output = resampled(input, 2, 2.0);
+        assert_eq!(output.len() % 2, 0);
+        // O canal direito constante só é tocado pelo transiente inicial do filtro
+        let right: Vec<f32> = output.iter().skip(1).step_by(2).skip(500).copied().collect();
+        assert!(right.iter().all(|s| (s - 0.25).abs() < 1e-3));
+    }
+
+    #[test]
+    fn filters_tones_above_the_new_nyquist() {
+        // 19.2 kHz a 3x cairia acima da nova Nyquist (8 kHz) e voltaria rebatido
+        let aliased = resampled(sine(19_200.0, 0.5), 1, 3.0);
+        assert!(rms(&aliased[1000..]) < 0.01, "rms {}", rms(&aliased[1000..]));
+        // 1 kHz fica abaixo do corte e passa inteiro
+        let kept = resampled(sine(1000.0, 0.5), 1, 3.0);
+        assert!((rms(&kept[1000..]) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.02, "rms {}", rms(&kept[1000..]));
+    }
+}
@@ -0,0 +1,203 @@
+// src/sample_tap.rs
+// Derivação das amostras que vão para a saída, para o visualizador e os medidores de nível.
+// A TappedSource é a última fonte da cadeia (envolve a SymphoniaSource e o que vier depois
+// dela) e copia cada frame (em mono) para um anel de atômicos que a UI lê no update.
+// Não há trava: a thread de áudio só escreve e avança o índice; a UI lê as últimas N amostras
+// (uma amostra sobrescrita no meio da leitura só borra um frame do desenho).
+// Com o painel fechado a cópia é desligada e a fonte só repassa as amostras.
+// Os medidores (pico/RMS por canal) são sempre calculados, em blocos, já com o volume do Sink
+// aplicado: o rodio multiplica as amostras pelo volume depois da nossa fonte.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+const RING_SIZE: usize = 8192; // Potência de 2; ~170 ms a 48 kHz
+// A fonte consulta `enabled` a cada bloco, não a cada amostra
+const ENABLED_CHECK_INTERVAL: usize = 1024;
+// Frames por bloco dos medidores (~20 ms a 48 kHz)
+const METER_BLOCK_FRAMES: usize = 1024;
+const METER_CHANNELS: usize = 2;
+
+// Níveis de um canal desde a última leitura (valores lineares, 0–1 = até o fundo de escala)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChannelLevel {
+    pub peak: f32,
+    pub rms: f32,
+    pub clipped: bool,
+}
+
+#[derive(Default)]
+struct MeterChannel {
+    peak_bits: AtomicU32, // Maior pico desde a leitura (f32 positivo: a ordem dos bits é a dos valores)
+    rms_bits: AtomicU32,  // RMS do último bloco
+    clipped: AtomicBool,
+}
+
+pub struct SampleTap {
+    enabled: AtomicBool,
+    samples: Vec<AtomicU32>, // f32 em bits
+    write_pos: AtomicUsize,  // Total de frames escritos (a posição no anel é módulo RING_SIZE)
+    sample_rate: AtomicU32,
+    volume_bits: AtomicU32,
+    meters: [MeterChannel; METER_CHANNELS],
+}
+
+impl Default for SampleTap {
+    fn default() -> Self {
+        SampleTap {
+            enabled: AtomicBool::new(false),
+            samples: (0..RING_SIZE).map(|_| AtomicU32::new(0)).collect(),
+            write_pos: AtomicUsize::new(0),
+            sample_rate: AtomicU32::new(44_100),
+            volume_bits: AtomicU32::new(1.0f32.to_bits()),
+            meters: Default::default(),
+        }
+    }
+}
+
+impl SampleTap {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn push(&self, sample: f32) {
+        let pos = self.write_pos.load(Ordering::Relaxed);
+        self.samples[pos % RING_SIZE].store(sample.to_bits(), Ordering::Relaxed);
+        self.write_pos.store(pos.wrapping_add(1), Ordering::Release);
+    }
+
+    // Mesmo volume passado ao Sink (ganho linear)
+    pub fn set_volume(&self, volume: f32) {
+        self.volume_bits.store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    fn volume(&self) -> f32 {
+        f32::from_bits(self.volume_bits.load(Ordering::Relaxed))
+    }
+
+    fn publish_block(&self, channel: usize, peak: f32, rms: f32) {
+        let meter = &self.meters[channel];
+        meter.peak_bits.fetch_max(peak.to_bits(), Ordering::Relaxed);
+        meter.rms_bits.store(rms.to_bits(), Ordering::Relaxed);
+        if peak >= 1.0 { meter.clipped.store(true, Ordering::Relaxed); }
+    }
+
+    // Níveis desde a última chamada (a UI chama uma vez por frame); zera picos e clip
+    pub fn take_levels(&self) -> [ChannelLevel; METER_CHANNELS] {
+        std::array::from_fn(|channel| {
+            let meter = &self.meters[channel];
+            ChannelLevel {
+                peak: f32::from_bits(meter.peak_bits.swap(0, Ordering::Relaxed)),
+                rms: f32::from_bits(meter.rms_bits.load(Ordering::Relaxed)),
+                clipped: meter.clipped.swap(false, Ordering::Relaxed),
+            }
+        })
+    }
+
+    // Sem áudio saindo (pausa, stop) o último bloco não deve ficar parado no medidor
+    pub fn clear_levels(&self) {
+        for meter in &self.meters { meter.rms_bits.store(0, Ordering::Relaxed); }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    // Copia as últimas `out.len()` amostras (mono, da mais antiga para a mais recente)
+    pub fn read_latest(&self, out: &mut [f32]) {
+        let count = out.len().min(RING_SIZE);
+        let end = self.write_pos.load(Ordering::Acquire);
+        let start = end.wrapping_sub(count);
+        for (i, slot) in out[..count].iter_mut().enumerate() {
+            *slot = f32::from_bits(self.samples[start.wrapping_add(i) % RING_SIZE].load(Ordering::Relaxed));
+        }
+    }
+}
+
+// --- Fonte que repassa as amostras e alimenta o tap ---
+pub struct TappedSource<S> {
+    inner: S,
+    tap: Arc<SampleTap>,
+    channels: usize,
+    enabled: bool,
+    until_check: usize,
+    frame_sum: f32,
+    frame_pos: usize,
+    // Medidores: pico e soma dos quadrados do bloco atual, por canal (mono vai para os dois)
+    volume: f32,
+    block_peak: [f32; METER_CHANNELS],
+    block_sum_sq: [f32; METER_CHANNELS],
+    block_frames: usize,
+}
+
+impl<S: Source<Item = f32>> TappedSource<S> {
+    pub fn new(inner: S, tap: Arc<SampleTap>) -> Self {
+        let channels = inner.channels().max(1) as usize;
+        tap.sample_rate.store(inner.sample_rate(), Ordering::Relaxed);
+        let volume = tap.volume();
+        TappedSource {
+            inner, tap, channels, enabled: false, until_check: 0, frame_sum: 0.0, frame_pos: 0,
+            volume, block_peak: [0.0; METER_CHANNELS], block_sum_sq: [0.0; METER_CHANNELS], block_frames: 0,
+        }
+    }
+
+    fn meter_sample(&mut self, sample: f32) {
+        let value = sample * self.volume;
+        let channels = if self.channels == 1 { 0..METER_CHANNELS } else { self.frame_pos..self.frame_pos + 1 };
+        for channel in channels.filter(|&c| c < METER_CHANNELS) {
+            self.block_peak[channel] = self.block_peak[channel].max(value.abs());
+            self.block_sum_sq[channel] += value * value;
+        }
+    }
+
+    fn finish_meter_block(&mut self) {
+        for channel in 0..METER_CHANNELS {
+            let rms = (self.block_sum_sq[channel] / self.block_frames as f32).sqrt();
+            self.tap.publish_block(channel, self.block_peak[channel], rms);
+        }
+        self.block_peak = [0.0; METER_CHANNELS];
+        self.block_sum_sq = [0.0; METER_CHANNELS];
+        self.block_frames = 0;
+        self.volume = self.tap.volume();
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for TappedSource<S> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next()?;
+        if self.until_check == 0 {
+            self.enabled = self.tap.is_enabled();
+            self.until_check = ENABLED_CHECK_INTERVAL;
+        }
+        self.until_check -= 1;
+        // O canal é contado sempre, para o frame não desalinhar quando o tap é religado
+        if self.enabled { self.frame_sum += sample; }
+        self.meter_sample(sample);
+        self.frame_pos += 1;
+        if self.frame_pos == self.channels {
+            if self.enabled { self.tap.push(self.frame_sum / self.channels as f32); }
+            self.frame_sum = 0.0;
+            self.frame_pos = 0;
+            self.block_frames += 1;
+            if self.block_frames == METER_BLOCK_FRAMES { self.finish_meter_block(); }
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for TappedSource<S> {
+    #[inline] fn current_frame_len(&self) -> Option<usize> { self.inner.current_frame_len() }
+    #[inline] fn channels(&self) -> u16 { self.inner.channels() }
+    #[inline] fn sample_rate(&self) -> u32 { self.inner.sample_rate() }
+    #[inline] fn total_duration(&self) -> Option<Duration> { self.inner.total_duration() }
+}
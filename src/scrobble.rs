@@ -0,0 +1,101 @@
+// src/scrobble.rs
+// Log de scrobbles offline: cada faixa ouvida por tempo suficiente vai para um
+// `.scrobbler.log` (formato Audioscrobbler 1.1, o mesmo do Rockbox, aceito por
+// ferramentas de envio para o Last.fm) e para um `listens.jsonl` no formato de
+// importação do ListenBrainz. Nada é enviado pela rede.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::library::APP_NAME;
+
+const SCROBBLER_LOG_FILE: &str = ".scrobbler.log";
+const LISTENBRAINZ_FILE: &str = "listens.jsonl";
+
+// Regras do Last.fm: faixas com mais de 30 s contam depois de metade da duração ou 4 minutos
+const MIN_TRACK_LENGTH: Duration = Duration::from_secs(30);
+const MAX_THRESHOLD: Duration = Duration::from_secs(4 * 60);
+
+// Tempo tocado a partir do qual a faixa conta como ouvida (None = curta demais)
+pub fn threshold(duration: Duration) -> Option<Duration> {
+    if duration <= MIN_TRACK_LENGTH { return None; }
+    Some((duration / 2).min(MAX_THRESHOLD))
+}
+
+pub struct Scrobble {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+    pub duration: Duration,
+    pub listened_at: i64, // Unix timestamp (UTC) do início da audição
+}
+
+// Pasta dos logs (a pasta de dados do app)
+pub fn log_dir() -> Option<PathBuf> {
+    eframe::storage_dir(APP_NAME)
+}
+
+// Acrescenta o scrobble aos dois logs
+pub fn append(scrobble: &Scrobble) -> Result<(), String> {
+    let dir = log_dir().ok_or("No data directory available")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+
+    let log_path = dir.join(SCROBBLER_LOG_FILE);
+    let mut text = String::new();
+    if !log_path.exists() {
+        text.push_str(&format!("#AUDIOSCROBBLER/1.1\n#TZ/UTC\n#CLIENT/{} {}\n", APP_NAME, env!("CARGO_PKG_VERSION")));
+    }
+    text.push_str(&scrobbler_log_line(scrobble));
+    append_to(&log_path, &text)?;
+
+    append_to(&dir.join(LISTENBRAINZ_FILE), &listenbrainz_line(scrobble))
+}
+
+fn append_to(path: &PathBuf, text: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    file.write_all(text.as_bytes()).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+// ARTISTA \t ÁLBUM \t TÍTULO \t FAIXA \t DURAÇÃO(s) \t L \t TIMESTAMP \t MBID
+// "L" = ouvida (o formato usa "S" para puladas, que não registramos)
+fn scrobbler_log_line(scrobble: &Scrobble) -> String {
+    let fields = [
+        log_field(&scrobble.artist),
+        log_field(scrobble.album.as_deref().unwrap_or("")),
+        log_field(&scrobble.title),
+        scrobble.track_number.map(|n| n.to_string()).unwrap_or_default(),
+        scrobble.duration.as_secs().to_string(),
+        "L".to_string(),
+        scrobble.listened_at.to_string(),
+        String::new(),
+    ];
+    format!("{}\n", fields.join("\t"))
+}
+
+// Tabs e quebras de linha separam campos e registros no log
+fn log_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+fn listenbrainz_line(scrobble: &Scrobble) -> String {
+    let mut additional_info = json!({
+        "duration_ms": scrobble.duration.as_millis() as u64,
+        "media_player": APP_NAME,
+        "submission_client": APP_NAME,
+        "submission_client_version": env!("CARGO_PKG_VERSION"),
+    });
+    if let Some(number) = scrobble.track_number { additional_info["tracknumber"] = json!(number); }
+    let mut track_metadata = json!({
+        "artist_name": scrobble.artist,
+        "track_name": scrobble.title,
+        "additional_info": additional_info,
+    });
+    if let Some(album) = &scrobble.album { track_metadata["release_name"] = json!(album); }
+    format!("{}\n", json!({ "listened_at": scrobble.listened_at, "track_metadata": track_metadata }))
+}
@@ -0,0 +1,397 @@
+// src/tag_writer.rs
+// Escrita de tags com Lofty: editor de tags e avaliações. Cada escrita é feita em uma cópia
+// do arquivo, que depois substitui o original com um rename. Assim o arquivo nunca fica
+// gravado pela metade e quem já estava com o original aberto continua lendo o original.
+// A cópia é do arquivo inteiro, por isso a UI manda as gravações para uma thread (TagWriteQueue).
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+
+use crossbeam_channel::{unbounded, Receiver as CrossbeamReceiver, Sender as CrossbeamSender};
+
+use lofty::config::{ParseOptions, WriteOptions};
+use lofty::flac::FlacFile;
+use lofty::id3::v2::{Frame, FrameId, Id3v2Tag, PopularimeterFrame, TextInformationFrame};
+use lofty::iff::wav::WavFile;
+use lofty::mpeg::MpegFile;
+use lofty::ogg::{OggPictureStorage, VorbisComments, VorbisFile};
+use lofty::picture::{Picture, PictureType};
+use lofty::prelude::*;
+use lofty::probe::Probe;
+use lofty::TextEncoding;
+
+use crate::playlist_io::cue;
+
+// E-mail do POPM usado pelo Windows Media Player, que a maioria dos players lê
+const POPM_EMAIL: &str = "Windows Media Player 9 Series";
+
+// --- Campos exibidos no editor ---
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagFields {
+    pub title: String,
+    pub artist: String,
+    pub album_artist: String,
+    pub album: String,
+    pub track: String,
+    pub disc: String,
+    pub year: String,
+    pub genre: String,
+    pub comment: String,
+    pub cover: Option<Vec<u8>>,
+}
+
+impl TagFields {
+    pub fn read(path: &Path) -> Result<TagFields, String> {
+        TagFields::read_with(path, true)
+    }
+
+    // Sem decodificar as capas (organizador: só os textos interessam)
+    pub fn read_without_cover(path: &Path) -> Result<TagFields, String> {
+        TagFields::read_with(path, false)
+    }
+
+    fn read_with(path: &Path, read_cover: bool) -> Result<TagFields, String> {
+        if cue::split_track_path(path).is_some() { return Err("CUE sheet tracks have no tags of their own".to_string()); }
+        let tagged_file = Probe::open(path)
+            .map(|probe| probe.options(ParseOptions::new().read_cover_art(read_cover)))
+            .and_then(|probe| probe.read())
+            .map_err(|e| format!("Failed to read tags from {:?}: {}", path, e))?;
+        let Some(tag) = tagged_file.primary_tag().or_else(|| tagged_file.first_tag()) else { return Ok(TagFields::default()); };
+        let text = |value: Option<Cow<'_, str>>| value.map(Cow::into_owned).unwrap_or_default();
+        let number = |value: Option<u32>| value.map(|n| n.to_string()).unwrap_or_default();
+        let cover = tag.pictures().iter().find(|p| p.pic_type() == PictureType::CoverFront).or_else(|| tag.pictures().first());
+        Ok(TagFields {
+            title: text(tag.title()),
+            artist: text(tag.artist()),
+            album_artist: tag.get_string(&ItemKey::AlbumArtist).unwrap_or_default().to_string(),
+            album: text(tag.album()),
+            track: number(tag.track()),
+            disc: number(tag.disk()),
+            year: number(tag.year()),
+            genre: text(tag.genre()),
+            comment: text(tag.comment()),
+            cover: cover.map(|p| p.data().to_vec()),
+        })
+    }
+}
+
+// --- Alterações a gravar ---
+// None = manter o valor de cada faixa; Some("") = apagar o campo
+#[derive(Debug, Clone, Default)]
+pub struct TagChanges {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album_artist: Option<String>,
+    pub album: Option<String>,
+    pub track: Option<String>,
+    pub disc: Option<String>,
+    pub year: Option<String>,
+    pub genre: Option<String>,
+    pub comment: Option<String>,
+    pub cover: CoverChange,
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum CoverChange {
+    #[default]
+    Keep,
+    Replace(Vec<u8>),
+    Remove,
+}
+
+impl TagChanges {
+    pub fn is_empty(&self) -> bool {
+        [&self.title, &self.artist, &self.album_artist, &self.album, &self.track, &self.disc, &self.year, &self.genre, &self.comment]
+            .iter().all(|change| change.is_none())
+            && matches!(self.cover, CoverChange::Keep)
+    }
+
+    // Números inválidos são recusados antes de tocar em qualquer arquivo
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, change) in [("Track", &self.track), ("Disc", &self.disc), ("Year", &self.year)] {
+            if let Some(value) = change.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                value.parse::<u32>().map_err(|_| format!("{} must be a number (got '{}')", name, value))?;
+            }
+        }
+        if let CoverChange::Replace(data) = &self.cover {
+            Picture::from_reader(&mut data.as_slice()).map_err(|e| format!("Unsupported cover image: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn write_tags(path: &Path, changes: &TagChanges) -> Result<(), String> {
+    let cover = match &changes.cover {
+        CoverChange::Replace(data) => {
+            let mut picture = Picture::from_reader(&mut data.as_slice()).map_err(|e| format!("Unsupported cover image: {}", e))?;
+            picture.set_pic_type(PictureType::CoverFront);
+            Some(picture)
+        }
+        _ => None,
+    };
+    let remove_cover = !matches!(changes.cover, CoverChange::Keep);
+    edit_file_safely(path, |file_path, file| {
+        let options = ParseOptions::new();
+        match extension(path).as_str() {
+            "mp3" => {
+                let mut audio = MpegFile::read_from(file, options).map_err(|e| e.to_string())?;
+                if audio.id3v2().is_none() { audio.set_id3v2(Id3v2Tag::new()); }
+                if let Some(tag) = audio.id3v2_mut() { apply_id3v2(tag, changes, remove_cover, cover.clone()); }
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            "wav" => {
+                let mut audio = WavFile::read_from(file, options).map_err(|e| e.to_string())?;
+                if audio.id3v2().is_none() { audio.set_id3v2(Id3v2Tag::new()); }
+                if let Some(tag) = audio.id3v2_mut() { apply_id3v2(tag, changes, remove_cover, cover.clone()); }
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            "flac" => {
+                let mut audio = FlacFile::read_from(file, options).map_err(|e| e.to_string())?;
+                if audio.vorbis_comments().is_none() { audio.set_vorbis_comments(VorbisComments::new()); }
+                if let Some(tag) = audio.vorbis_comments_mut() { apply_vorbis(tag, changes); }
+                // No FLAC as imagens ficam em blocos próprios, fora dos comentários
+                if remove_cover { remove_covers(&mut audio); }
+                if let Some(picture) = cover.clone() { audio.insert_picture(picture, None).map_err(|e| e.to_string())?; }
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            "ogg" => {
+                let mut audio = VorbisFile::read_from(file, options).map_err(|e| e.to_string())?;
+                let tag = audio.vorbis_comments_mut();
+                apply_vorbis(tag, changes);
+                if remove_cover { remove_covers(tag); }
+                if let Some(picture) = cover.clone() { tag.insert_picture(picture, None).map_err(|e| e.to_string())?; }
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            _ => Err("Unsupported file format".to_string()),
+        }
+    })
+}
+
+// Grava a nota (0–5) como FMPS_RATING (0.0–1.0) e, em ID3v2, também no POPM.
+// Nota 0 remove o FMPS_RATING e zera o POPM ("sem avaliação").
+pub fn write_rating(path: &Path, rating: u8) -> Result<(), String> {
+    if cue::split_track_path(path).is_some() {
+        return Err("Ratings of CUE sheet tracks are only kept in the player's database".to_string());
+    }
+    let fmps = (rating > 0).then(|| format!("{:.1}", rating.min(5) as f64 / 5.0));
+    edit_file_safely(path, |file_path, file| {
+        let options = ParseOptions::new();
+        match extension(path).as_str() {
+            "mp3" => {
+                let mut audio = MpegFile::read_from(file, options).map_err(|e| e.to_string())?;
+                if audio.id3v2().is_none() { audio.set_id3v2(Id3v2Tag::new()); }
+                if let Some(tag) = audio.id3v2_mut() { set_id3v2_rating(tag, rating, fmps); }
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            "wav" => {
+                let mut audio = WavFile::read_from(file, options).map_err(|e| e.to_string())?;
+                if audio.id3v2().is_none() { audio.set_id3v2(Id3v2Tag::new()); }
+                if let Some(tag) = audio.id3v2_mut() { set_id3v2_rating(tag, rating, fmps); }
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            "flac" => {
+                let mut audio = FlacFile::read_from(file, options).map_err(|e| e.to_string())?;
+                if audio.vorbis_comments().is_none() { audio.set_vorbis_comments(VorbisComments::new()); }
+                if let Some(tag) = audio.vorbis_comments_mut() { set_vorbis_rating(tag, fmps); }
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            "ogg" => {
+                let mut audio = VorbisFile::read_from(file, options).map_err(|e| e.to_string())?;
+                set_vorbis_rating(audio.vorbis_comments_mut(), fmps);
+                audio.save_to_path(file_path, WriteOptions::default()).map_err(|e| e.to_string())
+            }
+            _ => Err("Writing ratings is not supported for this format".to_string()),
+        }
+    })
+}
+
+// --- Gravação em segundo plano ---
+// O que gravar num arquivo: as tags do editor ou só a avaliação
+#[derive(Debug, Clone)]
+pub enum TagWrite {
+    Tags(Box<TagChanges>),
+    Rating(u8),
+}
+
+impl TagWrite {
+    fn apply(&self, path: &Path) -> Result<(), String> {
+        match self {
+            TagWrite::Tags(changes) => write_tags(path, changes),
+            TagWrite::Rating(rating) => write_rating(path, *rating),
+        }
+    }
+}
+
+// Fila de gravações atendida por uma thread, criada no primeiro pedido. Conta os resultados
+// até a fila esvaziar, para a UI avisar uma vez por lote.
+#[derive(Default)]
+pub struct TagWriteQueue {
+    request_sender: Option<CrossbeamSender<(PathBuf, TagWrite)>>,
+    result_receiver: Option<CrossbeamReceiver<(PathBuf, Result<(), String>)>>,
+    worker: Option<JoinHandle<()>>,
+    in_flight: usize,
+    saved: usize,
+    errors: Vec<String>,
+}
+
+impl TagWriteQueue {
+    fn ensure_worker(&mut self) {
+        if self.request_sender.is_some() { return; }
+        let (req_tx, req_rx) = unbounded::<(PathBuf, TagWrite)>();
+        let (res_tx, res_rx) = unbounded();
+        self.worker = Some(thread::spawn(move || {
+            for (path, write) in req_rx.iter() {
+                let result = write.apply(&path);
+                if res_tx.send((path, result)).is_err() { break; }
+            }
+        }));
+        self.request_sender = Some(req_tx);
+        self.result_receiver = Some(res_rx);
+    }
+
+    pub fn push(&mut self, path: PathBuf, write: TagWrite) {
+        self.ensure_worker();
+        if let Some(sender) = &self.request_sender {
+            if sender.send((path, write)).is_ok() { self.in_flight += 1; }
+        }
+    }
+
+    // Arquivos gravados desde a última consulta (os metadados deles precisam ser relidos)
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut written = Vec::new();
+        if let Some(receiver) = &self.result_receiver {
+            while let Ok((path, result)) = receiver.try_recv() {
+                self.in_flight = self.in_flight.saturating_sub(1);
+                match result {
+                    Ok(()) => { self.saved += 1; written.push(path); }
+                    Err(e) => self.errors.push(e),
+                }
+            }
+        }
+        written
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.in_flight > 0
+    }
+
+    // (gravados, erros) do lote, quando a fila esvaziou e houve algum resultado
+    pub fn take_report(&mut self) -> Option<(usize, Vec<String>)> {
+        if self.is_busy() || (self.saved == 0 && self.errors.is_empty()) { return None; }
+        Some((std::mem::take(&mut self.saved), std::mem::take(&mut self.errors)))
+    }
+
+    // Fecha a fila e espera as gravações pedidas (ao sair do app)
+    pub fn finish(&mut self) {
+        self.request_sender = None;
+        if let Some(worker) = self.worker.take() {
+            if worker.join().is_err() { eprintln!("Tags: Writer thread panicked"); }
+        }
+        self.poll();
+    }
+}
+
+// Copia o arquivo, deixa `edit` gravar na cópia e troca o original pela cópia
+fn edit_file_safely(path: &Path, edit: impl FnOnce(&Path, &mut fs::File) -> Result<(), String>) -> Result<(), String> {
+    let tmp_path = temp_path(path);
+    fs::copy(path, &tmp_path).map_err(|e| format!("Failed to copy {:?}: {}", path, e))?;
+    let result = fs::File::open(&tmp_path)
+        .map_err(|e| e.to_string())
+        .and_then(|mut file| edit(&tmp_path, &mut file));
+    match result {
+        Ok(()) => fs::rename(&tmp_path, path).map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            format!("Failed to replace {:?}: {}", path, e)
+        }),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(format!("Failed to write tags to {:?}: {}", path, e))
+        }
+    }
+}
+
+// ".musica.mp3.tagedit" na mesma pasta (o rename precisa ficar no mesmo disco)
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.tagedit", name))
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).unwrap_or_default()
+}
+
+// --- Campos comuns a todos os formatos ---
+fn apply_common<T: Accessor>(tag: &mut T, changes: &TagChanges) {
+    apply_text(tag, &changes.title, T::set_title, T::remove_title);
+    apply_text(tag, &changes.artist, T::set_artist, T::remove_artist);
+    apply_text(tag, &changes.album, T::set_album, T::remove_album);
+    apply_text(tag, &changes.genre, T::set_genre, T::remove_genre);
+    apply_text(tag, &changes.comment, T::set_comment, T::remove_comment);
+    apply_number(tag, &changes.track, T::set_track, T::remove_track);
+    apply_number(tag, &changes.disc, T::set_disk, T::remove_disk);
+    apply_number(tag, &changes.year, T::set_year, T::remove_year);
+}
+
+fn apply_text<T>(tag: &mut T, change: &Option<String>, set: fn(&mut T, String), remove: fn(&mut T)) {
+    match change.as_deref().map(str::trim) {
+        None => {}
+        Some("") => remove(tag),
+        Some(value) => set(tag, value.to_string()),
+    }
+}
+
+// Valores já validados em TagChanges::validate; um número inválido aqui é ignorado
+fn apply_number<T>(tag: &mut T, change: &Option<String>, set: fn(&mut T, u32), remove: fn(&mut T)) {
+    match change.as_deref().map(str::trim) {
+        None => {}
+        Some("") => remove(tag),
+        Some(value) => if let Ok(number) = value.parse() { set(tag, number) },
+    }
+}
+
+fn apply_id3v2(tag: &mut Id3v2Tag, changes: &TagChanges, remove_cover: bool, cover: Option<Picture>) {
+    apply_common(tag, changes);
+    let album_artist_id = FrameId::Valid(Cow::Borrowed("TPE2"));
+    match changes.album_artist.as_deref().map(str::trim) {
+        None => {}
+        Some("") => { tag.remove(&album_artist_id).for_each(drop); }
+        Some(value) => { tag.insert(Frame::Text(TextInformationFrame::new(album_artist_id, TextEncoding::UTF8, value.to_string()))); }
+    }
+    if remove_cover {
+        tag.remove_picture_type(PictureType::CoverFront);
+        tag.remove_picture_type(PictureType::Other);
+    }
+    if let Some(picture) = cover { tag.insert_picture(picture); }
+}
+
+fn apply_vorbis(tag: &mut VorbisComments, changes: &TagChanges) {
+    apply_common(tag, changes);
+    match changes.album_artist.as_deref().map(str::trim) {
+        None => {}
+        Some("") => { tag.remove("ALBUMARTIST").for_each(drop); }
+        Some(value) => tag.insert("ALBUMARTIST".to_string(), value.to_string()),
+    }
+}
+
+fn remove_covers(storage: &mut impl OggPictureStorage) {
+    storage.remove_picture_type(PictureType::CoverFront);
+    storage.remove_picture_type(PictureType::Other);
+}
+
+fn set_id3v2_rating(tag: &mut Id3v2Tag, rating: u8, fmps: Option<String>) {
+    let popm = match rating { 0 => 0, 1 => 1, 2 => 64, 3 => 128, 4 => 196, _ => 255 };
+    tag.insert(Frame::Popularimeter(PopularimeterFrame::new(POPM_EMAIL.to_string(), popm, 0)));
+    match fmps {
+        Some(value) => { tag.insert_user_text("FMPS_Rating".to_string(), value); }
+        None => { tag.remove_user_text("FMPS_Rating"); }
+    }
+}
+
+fn set_vorbis_rating(tag: &mut VorbisComments, fmps: Option<String>) {
+    match fmps {
+        Some(value) => tag.insert("FMPS_RATING".to_string(), value),
+        None => { tag.remove("FMPS_RATING").for_each(drop); }
+    }
+}
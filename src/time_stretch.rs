@@ -0,0 +1,254 @@
+// src/time_stretch.rs
+// Muda o andamento sem mudar a altura (WSOLA). A cada janela a fonte avança hop × andamento
+// na entrada e procura, perto desse ponto, o trecho mais parecido com a continuação natural
+// do trecho anterior; os trechos escolhidos são somados com janela de Hann (50% de sobreposição).
+// Em 100% a fonte só repassa as amostras, sem latência nem custo.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::playback_control::PlaybackControl;
+
+const WINDOW_MS: f32 = 40.0;
+const SEARCH_MS: f32 = 12.0; // Distância máxima (para cada lado) da posição nominal
+// A busca grossa anda de COARSE_STEP em COARSE_STEP frames e compara um frame a cada COARSE_STEP
+const COARSE_STEP: usize = 4;
+// No modo direto o andamento é consultado a cada tantos frames
+const TEMPO_CHECK_FRAMES: usize = 1024;
+
+pub struct TimeStretch<S> {
+    inner: S,
+    control: Arc<PlaybackControl>,
+    channels: usize,
+    window: Vec<f32>, // Hann periódica de 2 × hop frames
+    hop: usize,
+    tolerance: usize,
+    stretching: bool,
+    input: Vec<f32>, // Intercalada; só o trecho que a busca ainda pode alcançar
+    natural: usize, // Frame de `input` que continua o último trecho escolhido
+    nominal: f64, // Posição nominal do último trecho (frames de `input`)
+    tail: Vec<f32>, // Segunda metade do último trecho, já multiplicada pela janela
+    output: Vec<f32>,
+    output_pos: usize,
+    frame_pos: usize, // Canal da próxima amostra no modo direto (só troca de modo entre frames)
+    until_check: usize,
+}
+
+impl<S: Source<Item = f32>> TimeStretch<S> {
+    pub fn new(inner: S, control: Arc<PlaybackControl>) -> Self {
+        let channels = inner.channels().max(1) as usize;
+        let rate = inner.sample_rate() as f32;
+        let hop = ((rate * WINDOW_MS / 2000.0) as usize).max(COARSE_STEP);
+        let tolerance = (rate * SEARCH_MS / 1000.0) as usize;
+        let window = (0..2 * hop).map(|i| 0.5 - 0.5 * (std::f32::consts::PI * i as f32 / hop as f32).cos()).collect();
+        TimeStretch {
+            inner, control, channels, window, hop, tolerance, stretching: false,
+            input: Vec::new(), natural: 0, nominal: 0.0, tail: vec![0.0; hop * channels],
+            output: Vec::new(), output_pos: 0, frame_pos: 0, until_check: 0,
+        }
+    }
+
+    // Lê da fonte até `input` ter `frames` frames; false se ela acabou antes
+    fn fill(&mut self, frames: usize) -> bool {
+        while self.input.len() < frames * self.channels {
+            for _ in 0..self.channels {
+                match self.inner.next() {
+                    Some(sample) => self.input.push(sample),
+                    None => {
+                        let whole = self.input.len() / self.channels * self.channels;
+                        self.input.truncate(whole);
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // Começa como se o trecho anterior terminasse exatamente onde a fonte está
+    fn start_stretch(&mut self) {
+        self.input.clear();
+        if !self.fill(self.hop) {
+            self.output = std::mem::take(&mut self.input);
+            self.output_pos = 0;
+            return;
+        }
+        for (i, tail) in self.tail.iter_mut().enumerate() {
+            *tail = self.input[i] * self.window[self.hop + i / self.channels];
+        }
+        self.natural = 0;
+        self.nominal = -(self.hop as f64);
+        self.stretching = true;
+    }
+
+    // Volta ao modo direto. A cauda é a continuação natural já com a janela descendo, então
+    // repassar a entrada a partir de `natural` sem janela dá exatamente a soma das duas
+    fn finish_stretch(&mut self) {
+        self.output.clear();
+        self.output.extend_from_slice(&self.input[self.natural * self.channels..]);
+        self.output_pos = 0;
+        self.input.clear();
+        self.stretching = false;
+        self.frame_pos = 0;
+        self.until_check = TEMPO_CHECK_FRAMES;
+    }
+
+    // Gera os próximos `hop` frames em `output`
+    fn stretch_hop(&mut self) {
+        let tempo = self.control.stretch_tempo();
+        if tempo == 1.0 { self.finish_stretch(); return; }
+        self.nominal += self.hop as f64 * tempo as f64;
+        let center = self.nominal.round().max(0.0) as usize;
+        let (first, last) = (center.saturating_sub(self.tolerance), center + self.tolerance);
+        if !self.fill((last + 2 * self.hop).max(self.natural + self.hop)) {
+            // Fim da fonte: o que sobrou sai sem esticar
+            self.finish_stretch();
+            return;
+        }
+
+        let start = self.best_match(first, last);
+        let channels = self.channels;
+        self.output.clear();
+        self.output_pos = 0;
+        for i in 0..self.hop {
+            for c in 0..channels {
+                let slot = i * channels + c;
+                self.output.push(self.tail[slot] + self.input[(start + i) * channels + c] * self.window[i]);
+                self.tail[slot] = self.input[(start + self.hop + i) * channels + c] * self.window[self.hop + i];
+            }
+        }
+        self.natural = start + self.hop;
+
+        // Descarta o que nenhuma busca futura alcança (a posição nominal só avança)
+        let keep_from = self.natural.min((self.nominal.max(0.0) as usize).saturating_sub(self.tolerance));
+        if keep_from > 0 {
+            self.input.drain(..keep_from * channels);
+            self.natural -= keep_from;
+            self.nominal -= keep_from as f64;
+        }
+    }
+
+    // Trecho entre `first` e `last` mais parecido com a continuação natural (correlação normalizada),
+    // primeiro numa busca grossa e depois refinada em volta do melhor ponto
+    fn best_match(&self, first: usize, last: usize) -> usize {
+        let channels = self.channels;
+        let mono = |frame: usize| self.input[frame * channels..(frame + 1) * channels].iter().sum::<f32>();
+        let score = |candidate: usize, step: usize| {
+            let (mut dot, mut energy) = (0.0f32, 0.0f32);
+            for i in (0..self.hop).step_by(step) {
+                let value = mono(candidate + i);
+                dot += mono(self.natural + i) * value;
+                energy += value * value;
+            }
+            if energy <= f32::EPSILON { 0.0 } else { dot / energy.sqrt() }
+        };
+        let best_of = |candidates: &mut dyn Iterator<Item = usize>, step: usize| {
+            candidates.map(|candidate| (candidate, score(candidate, step)))
+                .fold((first, f32::NEG_INFINITY), |best, current| if current.1 > best.1 { current } else { best }).0
+        };
+        let coarse = best_of(&mut (first..=last).step_by(COARSE_STEP), COARSE_STEP);
+        let refine_from = coarse.saturating_sub(COARSE_STEP - 1).max(first);
+        best_of(&mut (refine_from..=(coarse + COARSE_STEP - 1).min(last)), 1)
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for TimeStretch<S> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        loop {
+            if self.output_pos < self.output.len() {
+                let sample = self.output[self.output_pos];
+                self.output_pos += 1;
+                return Some(sample);
+            }
+            if self.stretching {
+                self.stretch_hop();
+                continue;
+            }
+            if self.frame_pos == 0 {
+                if self.until_check == 0 {
+                    self.until_check = TEMPO_CHECK_FRAMES;
+                    if self.control.stretch_tempo() != 1.0 {
+                        self.start_stretch();
+                        continue;
+                    }
+                }
+                self.until_check -= 1;
+            }
+            let sample = self.inner.next()?;
+            self.frame_pos = (self.frame_pos + 1) % self.channels;
+            return Some(sample);
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Source for TimeStretch<S> {
+    // Esticando, os blocos não coincidem com os da fonte; o Sink usa então um tamanho fixo
+    #[inline] fn current_frame_len(&self) -> Option<usize> {
+        if self.stretching || self.output_pos < self.output.len() { None } else { self.inner.current_frame_len() }
+    }
+    #[inline] fn channels(&self) -> u16 { self.inner.channels() }
+    #[inline] fn sample_rate(&self) -> u32 { self.inner.sample_rate() }
+    #[inline] fn total_duration(&self) -> Option<Duration> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    const RATE: u32 = 44_100;
+
+    fn sine(freq: f32, seconds: f32) -> Vec<f32> {
+        (0..(RATE as f32 * seconds) as usize).map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / RATE as f32).sin()).collect()
+    }
+
+    fn stretched(samples: Vec<f32>, channels: u16, speed: f32) -> Vec<f32> {
+        let control = Arc::new(PlaybackControl::default());
+        control.set_speed(speed);
+        TimeStretch::new(SamplesBuffer::new(channels, RATE, samples), control).collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    // Frequência pelos cruzamentos de zero subindo
+    fn frequency(samples: &[f32]) -> f32 {
+        let crossings = samples.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count();
+        crossings as f32 * RATE as f32 / samples.len() as f32
+    }
+
+    #[test]
+    fn normal_speed_passes_samples_through() {
+        let input: Vec<f32> = (0..20_000).map(|i| (i as f32 * 0.37).sin() * 0.5).collect();
+        assert_eq!(stretched(input.clone(), 2, 1.0), input);
+    }
+
+    #[test]
+    fn changes_length_by_the_tempo() {
+        for speed in [0.5, 1.5, 2.0] {
+            let input = sine(440.0, 1.0);
+            let expected = input.len() as f32 / speed;
+            let output = stretched(input, 1, speed);
+            // Uma janela de folga no começo e no fim
+            let slack = RATE as f32 * WINDOW_MS / 1000.0 * 2.0;
+            assert!((output.len() as f32 - expected).abs() < slack, "speed {}: {} samples, expected about {}", speed, output.len(), expected);
+        }
+    }
+
+    #[test]
+    fn keeps_pitch_and_level() {
+        for speed in [0.5, 1.5] {
+            let output = stretched(sine(440.0, 1.0), 1, speed);
+            let middle = &output[output.len() / 4..output.len() * 3 / 4];
+            let freq = frequency(middle);
+            assert!((freq - 440.0).abs() < 440.0 * 0.02, "speed {}: {} Hz", speed, freq);
+            assert!((rms(middle) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.1, "speed {}: rms {}", speed, rms(middle));
+        }
+    }
+}
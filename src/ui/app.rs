@@ -1,6 +1,9 @@
 // /home/marcos/novprojeto/player/src/ui/app.rs
 
-use std::path::PathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use crossbeam_channel::{Sender as CrossbeamSender, Receiver as CrossbeamReceiver, TryRecvError as CrossbeamTryRecvError};
 use std::time::{Duration, Instant};
@@ -9,7 +12,40 @@ use eframe::egui;
 use rodio::Sink;
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
-use rand::Rng;
+
+use crate::files;
+use crate::library::{Bookmark, Library, ListenSession};
+use crate::metadata::{MetadataCache, TrackInfo};
+use crate::organizer::{self, PlannedMove, UndoLog};
+use crate::playback_clock::PlaybackClock;
+use crate::playlist_io::{self, cue, PlaylistEntry, PlaylistFormat};
+use crate::sample_tap::SampleTap;
+use crate::scrobble::{self, Scrobble};
+use crate::tag_writer::{TagChanges, TagWrite, TagWriteQueue};
+use crate::waveform::WaveformLoader;
+use super::bookmarks::{self, BookmarkAction};
+use super::level_meter::LevelMeters;
+use super::lyrics_view::LyricsView;
+use super::organize::{OrganizeAction, OrganizeWindow};
+use super::playback_speed::{self, PlaybackSpeed};
+use super::play_queue::PlayQueue;
+use super::playlist_table::{self, PlaylistColumn, PlaylistDragPayload, SortEntry, SortState};
+use super::playlist_tabs::{self, PlaylistTab};
+use super::practice::{self, AbLoop, PracticeAction};
+use super::rating;
+use super::search;
+use super::shuffle::{ShuffleCandidate, ShuffleMode, ShuffleOrder};
+use super::smart_playlist::{self, SmartCandidate, SmartEditor, SmartRules};
+use super::tag_editor::{TagEditor, TagEditorAction};
+use super::visualizer::{SpectrumStyle, Visualizer};
+use super::waveform_bar;
+
+// --- Playlists inteligentes: enquanto o leitor de tags trabalha, recalcula no máximo a cada tanto ---
+const SMART_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+// --- Retomada de arquivos longos: perto do começo ou do fim não há o que retomar ---
+const RESUME_MIN_POSITION: Duration = Duration::from_secs(10);
+const RESUME_END_MARGIN: Duration = Duration::from_secs(15);
 
 // --- Enum para Modos de Repetição ---
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -40,15 +76,37 @@ impl RepeatMode {
 }
 // --- Fim do Enum ---
 
+// --- Ações sobre a seleção da playlist (menu de contexto e atalhos) ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlaylistAction {
+    MoveToTop,
+    MoveToBottom,
+    MoveUp,
+    MoveDown,
+    PlayNext,
+    AddToQueue,
+    Remove,
+    CopyPaths,
+    ShowInFileManager,
+    SetRating(u8),
+    ToggleLoved,
+    EditTags,
+    Organize,
+}
+
 // --- Mensagens de Comunicação ---
 #[derive(Debug, Clone)]
 pub enum AudioCommand {
-    PlayTrack(PathBuf, usize),
+    PlayTrack(PathBuf, Option<usize>), // Índice na playlist que está tocando; None para faixas da fila
     Play,
     Pause,
     Stop,
     SetVolume(f32),
     Seek(Duration),
+    SetLoop(Option<(Duration, Duration)>), // Trecho A-B relativo ao início da faixa; None desliga
+    SetSpeed(f32), // Velocidade (0.5–3.0)
+    SetPreservePitch(bool), // A velocidade muda sem mudar a altura
+    SetPitchShift(f32), // Mudança de tom em semitons, independente da velocidade
 }
 
 #[derive(Debug)]
@@ -58,7 +116,7 @@ pub enum AudioResponse {
     PlaybackPaused,
     PlaybackStopped,
     PlaybackEnded,
-    CurrentlyPlaying(Option<usize>, Option<Duration>),
+    CurrentlyPlaying(Option<PathBuf>, Option<usize>, Option<Duration>), // Faixa carregada (None: nada tocando), índice do PlayTrack e duração
     SeekCompleted(Duration),
 }
 
@@ -67,11 +125,20 @@ pub enum AudioResponse {
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct MusicPlayerApp {
-    playlist: Vec<PathBuf>,
+    // --- Playlists em abas: a aba exibida e a aba que está tocando podem ser diferentes ---
+    playlists: Vec<PlaylistTab>,
+    active_playlist: usize,
+    playing_playlist: usize,
+    // Índice dentro da playlist que está tocando (playing_playlist)
     current_track_index: Option<usize>,
-    selected_track_index: Option<usize>,
+    // Playlist única salva por versões anteriores; migrada para a primeira aba em setup()
+    #[serde(rename = "playlist", skip_serializing)]
+    legacy_playlist: Vec<PathBuf>,
     volume: f32,
-    is_shuffle: bool,
+    shuffle_mode: ShuffleMode,
+    // Liga/desliga do shuffle salvo por versões anteriores; migrado em setup()
+    #[serde(rename = "is_shuffle", skip_serializing)]
+    legacy_is_shuffle: bool,
     repeat_mode: RepeatMode,
 
     // --- Adicionar estado para a janela "Sobre" ---
@@ -107,6 +174,118 @@ pub struct MusicPlayerApp {
     is_playing: bool,
     #[serde(skip)]
     is_paused: bool,
+
+    // --- Tabela da playlist: colunas visíveis (na ordem) ---
+    playlist_columns: Vec<PlaylistColumn>,
+
+    // Aba sendo renomeada e o texto em edição
+    #[serde(skip)]
+    renaming_playlist: Option<(usize, String)>,
+
+    // Aba ordenada antes de todas as tags chegarem; é reordenada quando a leitura terminar
+    #[serde(skip)]
+    resort_pending: Option<u64>,
+
+    // Retângulos das linhas desenhadas no último frame (para soltar arquivos externos na posição certa)
+    #[serde(skip)]
+    playlist_row_rects: Vec<(usize, egui::Rect)>,
+
+    // --- Busca na playlist ---
+    #[serde(skip)]
+    search_query: String,
+    // Filtros por avaliação: nota mínima e só faixas "loved"
+    #[serde(skip)]
+    rating_filter: u8,
+    #[serde(skip)]
+    loved_filter: bool,
+    #[serde(skip)]
+    metadata: MetadataCache,
+
+    // --- Fila de reprodução (tem prioridade sobre a ordem da playlist) ---
+    #[serde(skip)]
+    play_queue: PlayQueue,
+    // Faixa da fila tocando agora; enquanto isso current_track_index continua marcando
+    // a posição na playlist de onde a reprodução segue quando a fila esvaziar
+    #[serde(skip)]
+    queue_playing: Option<PathBuf>,
+    show_queue_panel: bool,
+    show_lyrics_panel: bool,
+    #[serde(skip)]
+    lyrics_view: LyricsView,
+    // Visualizador: o tap só copia amostras com o painel aberto
+    show_visualizer: bool,
+    spectrum_style: SpectrumStyle,
+    #[serde(skip)]
+    sample_tap: Arc<SampleTap>,
+    #[serde(skip)]
+    visualizer: Option<Visualizer>,
+    // Picos da forma de onda da faixa atual (barra de progresso)
+    #[serde(skip)]
+    waveform: WaveformLoader,
+    #[serde(skip)]
+    level_meters: LevelMeters,
+    // Modo de prática: laço A-B da faixa atual (a thread de áudio volta para A sozinha)
+    #[serde(skip)]
+    ab_loop: AbLoop,
+    // Velocidade, altura preservada e tom; o tempo decorrido anda na velocidade escolhida
+    playback_speed: PlaybackSpeed,
+    // Retomada: arquivos a partir de `resume_min_minutes` voltam para onde pararam
+    resume_long_files: bool,
+    resume_min_minutes: u32,
+    // Faixa pedida à thread de áudio; a retomada é feita no PlaybackStarted dela
+    #[serde(skip)]
+    resume_pending: Option<PathBuf>,
+    // Nome do marcador sendo digitado no menu
+    #[serde(skip)]
+    bookmark_name: String,
+    // Posição clicada com o botão direito na barra de progresso (menu do laço)
+    #[serde(skip)]
+    loop_menu_position: Option<Duration>,
+
+    // Permutação do shuffle e histórico do que foi ouvido
+    #[serde(skip)]
+    shuffle_order: ShuffleOrder,
+
+    // --- Histórico de reprodução e estatísticas (banco próprio, fora do estado do eframe) ---
+    #[serde(skip)]
+    library: Library,
+    #[serde(skip)]
+    current_listen: Option<ListenSession>,
+    #[serde(skip)]
+    show_history_window: bool,
+    // Exportação do histórico em andamento (feita numa thread; devolve o status ou o erro)
+    #[serde(skip)]
+    history_export: Option<CrossbeamReceiver<Result<String, String>>>,
+    // Posição e tempo tocado publicados pela thread de áudio
+    #[serde(skip)]
+    playback_clock: Arc<PlaybackClock>,
+    scrobble_log_enabled: bool,
+    // Grava as avaliações também nas tags (FMPS_RATING/POPM), além do banco
+    write_rating_tags: bool,
+
+    // --- Playlists inteligentes ---
+    // Chave do último recálculo (revisão do banco, metadados lidos, faixas das playlists comuns)
+    #[serde(skip)]
+    smart_refresh_key: Option<u64>,
+    #[serde(skip)]
+    smart_refreshed_at: Option<Instant>,
+    #[serde(skip)]
+    smart_editor: Option<SmartEditor>,
+
+    // --- Editor de tags ---
+    #[serde(skip)]
+    tag_editor: Option<TagEditor>,
+    // Gravações adiadas: a faixa estava aberta na thread de áudio (feitas ao sair, se ainda tocar)
+    #[serde(skip)]
+    pending_tag_writes: Vec<(PathBuf, TagWrite)>,
+    #[serde(skip)]
+    tag_writes: TagWriteQueue,
+
+    // --- Organizador de arquivos (modelo e pasta de destino lembrados) ---
+    organize_template: String,
+    organize_root: Option<PathBuf>,
+    #[serde(skip)]
+    organize_window: Option<OrganizeWindow>,
 }
 
 // --- Default impl ---
@@ -121,18 +300,64 @@ impl Default for MusicPlayerApp {
             audio_response_receiver: None,
             is_loading: false,
             loading_file_path: None,
-            playlist: Vec::new(),
+            playlists: vec![PlaylistTab::default()],
+            active_playlist: 0,
+            playing_playlist: 0,
             current_track_index: None,
-            selected_track_index: None,
+            legacy_playlist: Vec::new(),
             is_playing: false,
             is_paused: false,
             volume: 0.5,
-            is_shuffle: false,
+            shuffle_mode: ShuffleMode::Off,
+            legacy_is_shuffle: false,
             repeat_mode: RepeatMode::Off,
             show_about_window: false, // Janela começa fechada
             current_track_duration: None,
             playback_start_time: None,
             elapsed_duration_at_pause: Duration::ZERO,
+            playlist_columns: PlaylistColumn::ALL.to_vec(),
+            renaming_playlist: None,
+            resort_pending: None,
+            playlist_row_rects: Vec::new(),
+            search_query: String::new(),
+            rating_filter: 0,
+            loved_filter: false,
+            metadata: MetadataCache::default(),
+            play_queue: PlayQueue::default(),
+            queue_playing: None,
+            show_queue_panel: false,
+            show_lyrics_panel: false,
+            lyrics_view: LyricsView::default(),
+            show_visualizer: false,
+            spectrum_style: SpectrumStyle::default(),
+            sample_tap: Arc::new(SampleTap::default()),
+            visualizer: None,
+            waveform: WaveformLoader::default(),
+            level_meters: LevelMeters::default(),
+            ab_loop: AbLoop::default(),
+            playback_speed: PlaybackSpeed::default(),
+            resume_long_files: true,
+            resume_min_minutes: 20,
+            resume_pending: None,
+            bookmark_name: String::new(),
+            loop_menu_position: None,
+            shuffle_order: ShuffleOrder::default(),
+            library: Library::default(),
+            current_listen: None,
+            show_history_window: false,
+            history_export: None,
+            playback_clock: Arc::new(PlaybackClock::default()),
+            scrobble_log_enabled: true,
+            write_rating_tags: false,
+            smart_refresh_key: None,
+            smart_refreshed_at: None,
+            smart_editor: None,
+            tag_editor: None,
+            pending_tag_writes: Vec::new(),
+            tag_writes: TagWriteQueue::default(),
+            organize_template: organizer::DEFAULT_TEMPLATE.to_string(),
+            organize_root: None,
+            organize_window: None,
         }
     }
 }
@@ -145,15 +370,27 @@ impl MusicPlayerApp {
         sink: Arc<Mutex<Sink>>,
         sender: CrossbeamSender<AudioCommand>,
         receiver: CrossbeamReceiver<AudioResponse>,
+        playback_clock: Arc<PlaybackClock>,
+        sample_tap: Arc<SampleTap>,
     ) {
         self._stream_handle = Some(stream_handle);
+        self.playback_clock = playback_clock;
+        self.sample_tap = sample_tap;
         self.sink = sink;
         self.audio_command_sender = Some(sender);
         self.audio_response_receiver = Some(receiver);
         self.send_audio_command(AudioCommand::SetVolume(self.volume));
-        self.status = if self.playlist.is_empty() { "Ready. Add files to the playlist.".to_string() } else { "Playlist loaded. Ready.".to_string() };
-        if let Some(idx) = self.current_track_index { if idx >= self.playlist.len() { self.current_track_index = None; } }
-        if let Some(idx) = self.selected_track_index { if idx >= self.playlist.len() { self.selected_track_index = None; } }
+        self.send_playback_speed();
+        if self.playlists.is_empty() { self.playlists.push(PlaylistTab::default()); }
+        if !self.legacy_playlist.is_empty() && self.playlists.iter().all(|tab| tab.tracks.is_empty()) {
+            self.playlists[0].tracks = std::mem::take(&mut self.legacy_playlist);
+        }
+        self.library = Library::load();
+        if self.legacy_is_shuffle && self.shuffle_mode == ShuffleMode::Off { self.shuffle_mode = ShuffleMode::Tracks; }
+        if self.active_playlist >= self.playlists.len() { self.active_playlist = 0; }
+        if self.playing_playlist >= self.playlists.len() { self.playing_playlist = self.active_playlist; }
+        for tab in &mut self.playlists { tab.validate_selection(); }
+        self.status = if self.active_tab().tracks.is_empty() { "Ready. Add files to the playlist.".to_string() } else { "Playlist loaded. Ready.".to_string() };
         self.is_playing = false; self.is_paused = false; self.reset_progress_state(); self.current_track_index = None;
     }
 
@@ -176,35 +413,574 @@ impl MusicPlayerApp {
         }
     }
 
-    fn get_filename(&self, path: &PathBuf) -> String {
-        path.file_name()
-            .map(|name| name.to_string_lossy().into_owned())
-            .unwrap_or_else(|| path.display().to_string())
+    fn get_filename(&self, path: &Path) -> String {
+        display_name(path)
     }
 
-    fn play_random_track(&mut self) {
-        if self.playlist.is_empty() {
-            self.status = "Playlist is empty.".to_string();
+    // --- Abas de playlist ---
+    fn active_tab(&self) -> &PlaylistTab {
+        &self.playlists[self.active_playlist]
+    }
+
+    fn active_tab_mut(&mut self) -> &mut PlaylistTab {
+        &mut self.playlists[self.active_playlist]
+    }
+
+    // Faixas da playlist que está tocando (a que current_track_index indexa)
+    fn playing_tracks(&self) -> &[PathBuf] {
+        &self.playlists[self.playing_playlist].tracks
+    }
+
+    fn active_is_playing(&self) -> bool {
+        self.active_playlist == self.playing_playlist
+    }
+
+    // Faixa atual vista da aba exibida (None se outra aba estiver tocando)
+    fn current_index_in_active(&self) -> Option<usize> {
+        if self.active_is_playing() { self.current_track_index } else { None }
+    }
+
+    // Linha da aba exibida que está tocando (None enquanto toca uma faixa da fila)
+    fn playing_row_in_active(&self) -> Option<usize> {
+        if self.queue_playing.is_some() { None } else { self.current_index_in_active() }
+    }
+
+    fn has_playback(&self) -> bool {
+        self.is_playing || self.is_paused || self.is_loading
+    }
+
+    // Toca uma faixa da aba exibida, que passa a ser a playlist em reprodução
+    fn play_track_in_active(&mut self, index: usize) {
+        if !self.active_is_playing() {
+            self.playing_playlist = self.active_playlist;
+            self.current_track_index = None;
+        }
+        self.play_track_at_index(index);
+    }
+
+    fn add_playlist_tab(&mut self) {
+        let name = playlist_tabs::next_playlist_name(&self.playlists);
+        self.playlists.push(PlaylistTab::new(&name));
+        self.active_playlist = self.playlists.len() - 1;
+        self.status = format!("Created playlist '{}'.", name);
+    }
+
+    fn close_playlist_tab(&mut self, index: usize) {
+        if index >= self.playlists.len() { return; }
+        if index == self.playing_playlist && self.has_playback() {
+            self.send_audio_command(AudioCommand::Stop);
             self.reset_playback_state();
+        }
+        let removed = self.playlists.remove(index);
+        if self.playlists.is_empty() { self.playlists.push(PlaylistTab::default()); }
+        let fix = |i: usize| if i > index { i - 1 } else { i };
+        if self.playing_playlist == index { self.current_track_index = None; }
+        self.playing_playlist = fix(self.playing_playlist).min(self.playlists.len() - 1);
+        self.active_playlist = fix(self.active_playlist).min(self.playlists.len() - 1);
+        self.renaming_playlist = None;
+        self.status = format!("Closed playlist '{}'.", removed.name);
+    }
+
+    // Barra de abas: clique para trocar, duplo clique para renomear, botão direito para mais opções
+    fn show_playlist_tabs(&mut self, ui: &mut egui::Ui) {
+        let mut switch_to: Option<usize> = None;
+        let mut close_request: Option<usize> = None;
+        let mut add_request = false;
+        let playing_marker = if self.is_playing { "▶ " } else if self.is_paused { "⏸ " } else { "" };
+
+        ui.horizontal_wrapped(|ui| {
+            for index in 0..self.playlists.len() {
+                if let Some((rename_index, text)) = &mut self.renaming_playlist {
+                    if *rename_index == index {
+                        let edit = ui.add(egui::TextEdit::singleline(text).desired_width(110.0));
+                        edit.request_focus();
+                        let confirm = ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        let cancel = ui.input(|i| i.key_pressed(egui::Key::Escape));
+                        if confirm || (edit.lost_focus() && !cancel) {
+                            let new_name = text.trim().to_string();
+                            if !new_name.is_empty() { self.playlists[index].name = new_name; }
+                            self.renaming_playlist = None;
+                        } else if cancel {
+                            self.renaming_playlist = None;
+                        }
+                        continue;
+                    }
+                }
+
+                let tab = &self.playlists[index];
+                let marker = if index == self.playing_playlist { playing_marker } else { "" };
+                let smart_marker = if tab.is_smart() { "✨ " } else { "" };
+                let label = format!("{}{}{} ({})", marker, smart_marker, tab.name, tab.tracks.len());
+                let smart_summary = tab.smart.as_ref().map(SmartRules::summary);
+                let response = ui.selectable_label(index == self.active_playlist, label);
+                if response.clicked() { switch_to = Some(index); }
+                if response.double_clicked() { self.renaming_playlist = Some((index, self.playlists[index].name.clone())); }
+                let response = if !marker.is_empty() { response.on_hover_text("Currently playing") } else { response };
+                let response = match smart_summary { Some(summary) => response.on_hover_text(format!("Smart playlist: {}", summary)), None => response };
+                response.context_menu(|ui| {
+                    if ui.button("✏ Rename").clicked() {
+                        self.renaming_playlist = Some((index, self.playlists[index].name.clone()));
+                        ui.close_menu();
+                    }
+                    if let Some(rules) = &self.playlists[index].smart {
+                        if ui.button("⚙ Edit rules").clicked() {
+                            let tab = &self.playlists[index];
+                            self.smart_editor = Some(SmartEditor { tab_id: Some(tab.id), name: tab.name.clone(), rules: rules.clone() });
+                            ui.close_menu();
+                        }
+                    }
+                    if ui.button("✖ Close playlist").clicked() {
+                        close_request = Some(index);
+                        ui.close_menu();
+                    }
+                });
+            }
+            if ui.button("➕").on_hover_text("New playlist").clicked() { add_request = true; }
+            if ui.button("✨").on_hover_text("New smart playlist").clicked() {
+                self.smart_editor = Some(SmartEditor { tab_id: None, name: "Smart playlist".to_string(), rules: SmartRules::default() });
+            }
+        });
+
+        if let Some(index) = switch_to { self.active_playlist = index; }
+        if let Some(index) = close_request { self.close_playlist_tab(index); }
+        if add_request { self.add_playlist_tab(); }
+    }
+
+    // --- Playlists inteligentes ---
+    // As faixas de uma aba inteligente vêm das regras; edições manuais são recusadas
+    fn smart_tab_is_read_only(&mut self) -> bool {
+        if !self.active_tab().is_smart() { return false; }
+        self.status = "Smart playlists are filled by their rules; edit the rules or use a regular playlist.".to_string();
+        true
+    }
+
+    // Recalcula as abas inteligentes quando o banco, os metadados lidos ou as playlists comuns mudam
+    fn refresh_smart_playlists(&mut self, force: bool) {
+        if !self.playlists.iter().any(PlaylistTab::is_smart) { return; }
+        let now = chrono::Local::now().timestamp();
+        let mut hasher = DefaultHasher::new();
+        // A hora entra na chave para "não tocada em N dias" avançar sozinha
+        (self.library.revision(), self.metadata.revision(), now / 3600).hash(&mut hasher);
+        for tab in self.playlists.iter().filter(|tab| !tab.is_smart()) { (tab.id, tab.revision()).hash(&mut hasher); }
+        let key = hasher.finish();
+        if !force && self.smart_refresh_key == Some(key) { return; }
+        // Com as tags chegando a cada frame a chave muda sempre; a última leitura dispara o recálculo final
+        let recent = self.smart_refreshed_at.is_some_and(|at| at.elapsed() < SMART_REFRESH_INTERVAL);
+        if !force && recent && self.metadata.is_loading() { return; }
+        self.smart_refresh_key = Some(key);
+        self.smart_refreshed_at = Some(Instant::now());
+
+        let paths: Vec<PathBuf> = smart_playlist::unique_paths(
+            self.playlists.iter().filter(|tab| !tab.is_smart()).flat_map(|tab| tab.tracks.iter()).chain(self.library.stats.keys())
+        ).into_iter().cloned().collect();
+        if self.playlists.iter().filter_map(|tab| tab.smart.as_ref()).any(SmartRules::needs_tags) {
+            for path in &paths { self.metadata.request(path); }
+        }
+        let candidates: Vec<SmartCandidate<'_>> = paths.iter()
+            .map(|path| SmartCandidate { path, info: self.metadata.get(path), stats: self.library.stats(path) })
+            .collect();
+        let results: Vec<(usize, Vec<PathBuf>)> = self.playlists.iter().enumerate()
+            .filter_map(|(index, tab)| tab.smart.as_ref().map(|rules| (index, rules.evaluate(&candidates, &tab.tracks, now))))
+            .collect();
+
+        for (index, mut tracks) in results {
+            let tab = &self.playlists[index];
+            // A faixa tocando agora continua na lista até terminar
+            let current_path = if index == self.playing_playlist { self.current_track_index.and_then(|i| tab.tracks.get(i).cloned()) } else { None };
+            if let (Some(current), Some(current_index)) = (&current_path, self.current_track_index) {
+                if !tracks.contains(current) { tracks.insert(current_index.min(tracks.len()), current.clone()); }
+            }
+            if tracks == tab.tracks { continue; }
+            if current_path.is_some() { self.current_track_index = current_path.and_then(|path| tracks.iter().position(|t| *t == path)); }
+            let tab = &mut self.playlists[index];
+            let focused = tab.selected_track_index.and_then(|i| tab.tracks.get(i).cloned());
+            tab.tracks = tracks;
+            tab.sort = None;
+            tab.select_single(focused.and_then(|path| tab.tracks.iter().position(|t| *t == path)));
+        }
+    }
+
+    fn show_smart_editor(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.smart_editor else { return; };
+        let mut open = true;
+        let mut save = false;
+        let mut cancel = false;
+        let title = if editor.tab_id.is_some() { "Edit smart playlist" } else { "New smart playlist" };
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut editor.name);
+                });
+                ui.weak("Tracks must match all rules. Candidates are the tracks of every regular playlist and the player's history.");
+                ui.separator();
+                smart_playlist::rules_editor(ui, &mut editor.rules);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Save").clicked() { save = true; }
+                    if ui.button("Cancel").clicked() { cancel = true; }
+                });
+            });
+        if save {
+            if let Some(editor) = self.smart_editor.take() { self.save_smart_playlist(editor); }
+        } else if cancel || !open {
+            self.smart_editor = None;
+        }
+    }
+
+    fn save_smart_playlist(&mut self, editor: SmartEditor) {
+        let name = if editor.name.trim().is_empty() { "Smart playlist".to_string() } else { editor.name.trim().to_string() };
+        match editor.tab_id.and_then(|id| self.playlists.iter().position(|tab| tab.id == id)) {
+            Some(index) => {
+                let tab = &mut self.playlists[index];
+                tab.name = name.clone();
+                tab.smart = Some(editor.rules);
+            }
+            None => {
+                self.playlists.push(PlaylistTab::new_smart(&name, editor.rules));
+                self.active_playlist = self.playlists.len() - 1;
+            }
+        }
+        self.status = format!("Saved smart playlist '{}'.", name);
+        self.refresh_smart_playlists(true);
+    }
+
+    // --- Editor de tags ---
+    fn show_tag_editor(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.tag_editor else { return; };
+        match editor.show(ctx) {
+            Some(TagEditorAction::Save(changes)) => {
+                if let Err(e) = changes.validate() {
+                    self.error_message = Some(e);
+                    return;
+                }
+                let paths = self.tag_editor.take().map(|editor| editor.paths).unwrap_or_default();
+                if changes.is_empty() { self.status = "No tag changes to save.".to_string(); return; }
+                self.save_tags(paths, *changes);
+            }
+            Some(TagEditorAction::Close) => self.tag_editor = None,
+            None => {}
+        }
+    }
+
+    fn save_tags(&mut self, paths: Vec<PathBuf>, changes: TagChanges) {
+        let count = paths.len();
+        let deferred = paths.into_iter().filter(|path| self.queue_tag_write(path.clone(), TagWrite::Tags(Box::new(changes.clone())))).count();
+        self.status = if deferred > 0 {
+            format!("Saving tags of {} track(s); the playing track will be updated when it stops.", count - deferred)
+        } else {
+            format!("Saving tags of {} track(s)...", count)
+        };
+    }
+
+    // Toda gravação de tags passa por aqui: a faixa aberta na thread de áudio só é regravada
+    // quando deixar de tocar; as outras vão para a thread de gravação. True se foi adiada.
+    fn queue_tag_write(&mut self, path: PathBuf, write: TagWrite) -> bool {
+        if self.audio_open_path().as_ref() == Some(&path) {
+            self.pending_tag_writes.push((path, write));
+            return true;
+        }
+        self.tag_writes.push(path, write);
+        false
+    }
+
+    // Resultados da thread de gravação: metadados relidos e um aviso por lote
+    fn poll_tag_writes(&mut self) {
+        for path in self.tag_writes.poll() { self.metadata.invalidate(&path); }
+        let Some((saved, errors)) = self.tag_writes.take_report() else { return; };
+        self.status = format!("Saved tags of {} track(s).", saved);
+        if !errors.is_empty() {
+            eprintln!("Tags: {}", errors.join("; "));
+            self.error_message = Some(format!("{} track(s) could not be saved: {}", errors.len(), errors[0]));
+        }
+    }
+
+    // Arquivo que a thread de áudio pode estar lendo agora
+    fn audio_open_path(&self) -> Option<PathBuf> {
+        self.loading_file_path.clone().or_else(|| self.now_playing_path())
+    }
+
+    fn apply_pending_tag_writes(&mut self) {
+        if self.pending_tag_writes.is_empty() { return; }
+        let open_path = self.audio_open_path();
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_tag_writes).into_iter().partition(|(path, _)| open_path.as_ref() != Some(path));
+        self.pending_tag_writes = waiting;
+        for (path, write) in ready { self.tag_writes.push(path, write); }
+    }
+
+    // Ao sair: a faixa ainda tocando é parada e as gravações adiadas são feitas antes de fechar
+    fn flush_tag_writes(&mut self) {
+        if !self.pending_tag_writes.is_empty() {
+            self.send_audio_command(AudioCommand::Stop);
+            println!("Tags: Writing {} deferred change(s) before exiting", self.pending_tag_writes.len());
+            for (path, write) in std::mem::take(&mut self.pending_tag_writes) { self.tag_writes.push(path, write); }
+        }
+        self.tag_writes.finish();
+        if let Some((_, errors)) = self.tag_writes.take_report() {
+            for error in errors { eprintln!("Tags: {}", error); }
+        }
+    }
+
+    // --- Organizador de arquivos ---
+    fn open_organizer(&mut self, paths: Vec<PathBuf>) {
+        if paths.is_empty() {
+            self.status = "CUE sheet tracks can't be organized; move the album file and its .cue together.".to_string();
             return;
         }
-        let mut rng = rand::thread_rng();
-        let playlist_len = self.playlist.len();
-        let mut random_index = rng.gen_range(0..playlist_len);
-        if playlist_len > 1 && Some(random_index) == self.current_track_index {
-             random_index = (random_index + rng.gen_range(1..playlist_len)) % playlist_len;
+        // Sem pasta lembrada: a pasta comum às faixas selecionadas
+        let root = self.organize_root.clone().filter(|dir| dir.is_dir()).unwrap_or_else(|| common_parent(&paths));
+        self.organize_window = Some(OrganizeWindow::open(paths, self.organize_template.clone(), root, self.audio_open_path()));
+    }
+
+    fn show_organizer(&mut self, ctx: &egui::Context) {
+        let in_use = self.audio_open_path();
+        let Some(window) = &mut self.organize_window else { return; };
+        window.set_in_use(in_use);
+        let action = window.show(ctx);
+        self.organize_template = window.template.clone();
+        self.organize_root = Some(window.root.clone());
+        match action {
+            // Uma gravação de tags em andamento recriaria o arquivo no caminho antigo
+            Some(OrganizeAction::Run { .. }) if self.tag_writes.is_busy() => {
+                self.status = "Tags are still being saved; try again in a moment.".to_string();
+            }
+            Some(OrganizeAction::Run { planned, root }) => {
+                self.organize_window = None;
+                self.run_organize(&planned, &root);
+            }
+            Some(OrganizeAction::Close) => self.organize_window = None,
+            None => {}
+        }
+    }
+
+    fn run_organize(&mut self, planned: &[PlannedMove], root: &Path) {
+        let in_use = self.audio_open_path();
+        let (moved, errors) = organizer::execute(planned, root, in_use.as_deref());
+        self.remap_moved_paths(&moved);
+        self.status = format!("Moved {} file(s). The last organize can be undone from the menu bar.", moved.len());
+        self.report_move_errors(&errors);
+    }
+
+    fn undo_organize(&mut self) {
+        match UndoLog::undo_last() {
+            Ok((restored, errors)) => {
+                self.remap_moved_paths(&restored);
+                self.status = format!("Moved {} file(s) back.", restored.len());
+                self.report_move_errors(&errors);
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    fn report_move_errors(&mut self, errors: &[String]) {
+        if errors.is_empty() { return; }
+        for error in errors { eprintln!("Organize: {}", error); }
+        self.error_message = Some(format!("{} file(s) could not be moved: {}", errors.len(), errors[0]));
+    }
+
+    // Todo caminho guardado pelo player que apontava para um arquivo movido passa a apontar para o novo
+    fn remap_moved_paths(&mut self, moves: &[(PathBuf, PathBuf)]) {
+        if moves.is_empty() { return; }
+        for (from, to) in moves {
+            for tab in &mut self.playlists { tab.rename_path(from, to); }
+            self.play_queue.rename_path(from, to);
+            self.shuffle_order.rename_path(from, to);
+            self.library.rename_path(from, to);
+            self.metadata.invalidate(from);
+            if self.queue_playing.as_ref() == Some(from) { self.queue_playing = Some(to.clone()); }
+            if let Some(listen) = self.current_listen.as_mut().filter(|listen| listen.path == *from) { listen.path = to.clone(); }
+            for (path, _) in self.pending_tag_writes.iter_mut().filter(|(path, _)| path == from) { *path = to.clone(); }
+        }
+        if let Err(e) = self.library.save() { eprintln!("Library: {}", e); }
+        self.refresh_smart_playlists(true);
+    }
+
+    // --- Importar/Exportar playlists ---
+    // A playlist importada vira uma nova aba com o nome do arquivo
+    fn import_playlist(&mut self) {
+        let Some(path) = FileDialog::new().add_filter("Playlists", &PlaylistFormat::EXTENSIONS).pick_file() else { return; };
+        match playlist_io::read_playlist(&path) {
+            Ok(entries) => {
+                let missing = entries.iter().filter(|entry| !entry.path.exists()).count();
+                let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| playlist_tabs::next_playlist_name(&self.playlists));
+                let mut tab = PlaylistTab::new(&name);
+                tab.tracks = entries.into_iter().map(|entry| entry.path).collect();
+                tab.validate_selection();
+                let count = tab.tracks.len();
+                self.playlists.push(tab);
+                self.active_playlist = self.playlists.len() - 1;
+                self.error_message = None;
+                self.status = if missing > 0 {
+                    format!("Imported {} track(s) from '{}' ({} not found on disk).", count, self.get_filename(&path), missing)
+                } else {
+                    format!("Imported {} track(s) from '{}'.", count, self.get_filename(&path))
+                };
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to import '{}': {}", path.display(), e));
+            }
+        }
+    }
+
+    fn export_playlist(&mut self) {
+        let default_name = format!("{}.m3u8", self.active_tab().name);
+        let Some(path) = FileDialog::new().add_filter("Playlists", &PlaylistFormat::EXTENSIONS).set_file_name(default_name).save_file() else { return; };
+        let entries = self.playlist_entries_for_export();
+        let skipped = self.active_tab().tracks.len() - entries.len();
+        match playlist_io::write_playlist(&path, &entries) {
+            Ok(()) => {
+                self.error_message = None;
+                self.status = format!("Exported {} track(s) to '{}'.", entries.len(), self.get_filename(&path));
+                if skipped > 0 { self.status.push_str(&format!(" Skipped {} CUE sheet track(s), which other players can't open.", skipped)); }
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to export '{}': {}", path.display(), e));
+            }
+        }
+    }
+
+    // Entradas da aba exibida com título ("Artista - Título") e duração lidos das tags.
+    // Faixas de CUE sheet ficam de fora: o caminho virtual `<álbum.cue>/NN` só existe aqui
+    fn playlist_entries_for_export(&mut self) -> Vec<PlaylistEntry> {
+        let tracks = self.active_tab().tracks.clone();
+        tracks.into_iter().filter(|path| cue::split_track_path(path).is_none()).map(|path| {
+            let info = self.metadata.load_now(&path);
+            let title = match (&info.artist, &info.title) {
+                (Some(artist), Some(title)) => Some(format!("{} - {}", artist, title)),
+                (None, Some(title)) => Some(title.clone()),
+                _ => None,
+            };
+            PlaylistEntry { title, duration: info.duration, path }
+        }).collect()
+    }
+
+    // Próxima faixa da permutação do shuffle; a volta só recomeça com RepeatMode::Playlist
+    fn play_shuffled_track(&mut self) {
+        let wrap = self.repeat_mode == RepeatMode::Playlist;
+        let tracks = self.playing_tracks().to_vec();
+        let candidates = self.shuffle_candidates(&tracks);
+        let playlist_id = self.playlists[self.playing_playlist].id;
+        match self.shuffle_order.next(playlist_id, &tracks, &candidates, self.shuffle_mode, wrap) {
+            Some(index) => self.play_track_at_index(index),
+            None => {
+                println!("play_shuffled_track: Every track has played.");
+                self.status = "Playlist finished.".to_string();
+                self.reset_playback_state();
+            }
+        }
+    }
+
+    // Álbum, artista e peso de cada faixa para o modo de shuffle atual. Só usa as tags já
+    // lidas; as que faltam são pedidas em segundo plano (até lá o álbum é a pasta)
+    fn shuffle_candidates(&mut self, tracks: &[PathBuf]) -> Vec<ShuffleCandidate> {
+        if self.shuffle_mode.needs_tags() { self.request_playing_tags(); }
+        tracks.iter().map(|path| {
+            let info = self.metadata.get(path);
+            let folder = || path.parent().map(|p| p.display().to_string()).unwrap_or_default();
+            ShuffleCandidate {
+                album: info.and_then(|i| i.album.clone()).unwrap_or_else(folder),
+                artist: info.and_then(|i| i.artist.clone()),
+                weight: self.library.stats(path).map_or(1.0, |stats| stats.shuffle_weight()),
+            }
+        }).collect()
+    }
+
+    fn set_shuffle_mode(&mut self, mode: ShuffleMode) {
+        if mode.is_on() && !self.shuffle_mode.is_on() {
+            let current = self.current_track_index.and_then(|idx| self.playing_tracks().get(idx).cloned());
+            self.shuffle_order.start_cycle(current.as_deref());
         }
-        self.play_track_at_index(random_index);
+        self.shuffle_mode = mode;
+        // Lidas já ao escolher o modo, para estarem prontas quando a próxima faixa for sorteada
+        if mode.needs_tags() { self.request_playing_tags(); }
+        self.status = format!("Shuffle mode: {}", mode.description());
+    }
+
+    fn request_playing_tags(&mut self) {
+        for path in &self.playlists[self.playing_playlist].tracks { self.metadata.request(path); }
+    }
+
+    // --- Histórico de reprodução ---
+    // Encerra a audição atual: completa quando a faixa terminou (PlaybackEnded),
+    // pulada quando foi interrompida (Next, Stop, outra faixa escolhida)
+    fn finish_listen(&mut self, completed: bool) {
+        self.remember_resume_position(completed);
+        if let Some(listen) = self.current_listen.take() {
+            self.library.record_listen(listen.finish(self.playback_clock.played(), completed));
+        }
+    }
+
+    // Registra a audição no log de scrobbles quando o tempo realmente tocado passa do limite
+    fn check_scrobble(&mut self) {
+        if !self.scrobble_log_enabled { return; }
+        let Some(listen) = &self.current_listen else { return; };
+        if listen.scrobbled { return; }
+        let Some(duration) = self.current_track_duration else { return; };
+        let Some(threshold) = scrobble::threshold(duration) else { return; };
+        if self.playback_clock.played() < threshold { return; }
+
+        let path = listen.path.clone();
+        let listened_at = listen.started_at();
+        // Tags ainda não lidas: pede em segundo plano e tenta de novo no próximo quadro
+        let Some(info) = self.metadata.get(&path) else {
+            self.metadata.request(&path);
+            return;
+        };
+        let (Some(artist), Some(title)) = (info.artist.clone(), info.title.clone()) else {
+            println!("Scrobble: Skipping '{}' (no artist/title tags)", path.display());
+            if let Some(listen) = &mut self.current_listen { listen.scrobbled = true; }
+            return;
+        };
+        let entry = Scrobble { artist, title, album: info.album.clone(), track_number: info.track_number, duration, listened_at };
+        if let Some(listen) = &mut self.current_listen { listen.scrobbled = true; }
+        // Escrita no log fora da thread da interface
+        std::thread::spawn(move || match scrobble::append(&entry) {
+            Ok(()) => println!("Scrobble: Logged '{} - {}'", entry.artist, entry.title),
+            Err(e) => eprintln!("Scrobble: {}", e),
+        });
+    }
+
+    fn start_listen(&mut self, path: PathBuf) {
+        self.finish_listen(false);
+        self.current_listen = Some(ListenSession::start(path));
+    }
+
+    // --- Avaliações ---
+    fn set_rating(&mut self, paths: &[PathBuf], rating: u8) {
+        let mut deferred = false;
+        for path in paths {
+            self.library.set_rating(path, rating);
+            if self.write_rating_tags { deferred |= self.queue_tag_write(path.clone(), TagWrite::Rating(rating)); }
+        }
+        self.status = if rating == 0 { format!("Cleared rating of {} track(s).", paths.len()) } else { format!("Rated {} track(s) {}.", paths.len(), rating::stars_text(rating)) };
+        if deferred { self.status.push_str(" The playing track's tags will be updated when it stops."); }
+    }
+
+    fn set_loved(&mut self, paths: &[PathBuf], loved: bool) {
+        for path in paths { self.library.set_loved(path, loved); }
+        self.status = format!("{} {} track(s).", if loved { "Loved" } else { "Unloved" }, paths.len());
+    }
+
+    // Faixa tocando agora (da fila ou da playlist)
+    fn now_playing_path(&self) -> Option<PathBuf> {
+        if !self.is_playing && !self.is_paused { return None; }
+        self.queue_playing.clone().or_else(|| self.current_track_index.and_then(|idx| self.playing_tracks().get(idx).cloned()))
     }
 
     fn play_track_at_index(&mut self, index: usize) {
-         if let Some(path_ref) = self.playlist.get(index) {
+         if let Some(path_ref) = self.playing_tracks().get(index) {
             let path_to_play = path_ref.clone();
             self.status = format!("Requesting play: {}", self.get_filename(&path_to_play));
+            self.finish_listen(false);
+            self.queue_playing = None;
             self.is_loading = true;
             self.loading_file_path = Some(path_to_play.clone());
             self.reset_progress_state();
-            self.send_audio_command(AudioCommand::PlayTrack(path_to_play, index));
+            self.resume_pending = Some(path_to_play.clone());
+            self.send_audio_command(AudioCommand::PlayTrack(path_to_play, Some(index)));
         } else {
             self.status = format!("Error: Could not find track at index {}", index);
             eprintln!("Error: play_track_at_index called with invalid index {}", index);
@@ -212,19 +988,53 @@ impl MusicPlayerApp {
         }
     }
 
+    // --- Fila de reprodução ---
+    // Toca a próxima faixa da fila. Retorna false se a fila estiver vazia.
+    fn play_queued_track(&mut self) -> bool {
+        let Some(path) = self.play_queue.pop_front() else { return false; };
+        self.play_path_from_queue(path);
+        true
+    }
+
+    fn play_path_from_queue(&mut self, path: PathBuf) {
+        self.status = format!("Requesting play: {}", self.get_filename(&path));
+        self.finish_listen(false);
+        self.is_loading = true;
+        self.loading_file_path = Some(path.clone());
+        self.queue_playing = Some(path.clone());
+        self.reset_progress_state();
+        self.resume_pending = Some(path.clone());
+        // Sem índice: a posição na playlist (current_track_index) continua onde estava
+        self.send_audio_command(AudioCommand::PlayTrack(path, None));
+    }
+
+    // "Play next" / "Add to queue" com a seleção da aba exibida
+    fn queue_selection(&mut self, selection: &[usize], play_next: bool) {
+        let paths: Vec<PathBuf> = selection.iter().filter_map(|&i| self.active_tab().tracks.get(i).cloned()).collect();
+        let count = paths.len();
+        if play_next {
+            self.play_queue.push_front(paths);
+            self.status = format!("{} track(s) will play next.", count);
+        } else {
+            self.play_queue.push_back(paths);
+            self.status = format!("Added {} track(s) to the queue.", count);
+        }
+    }
+
     fn play_next_track(&mut self) {
-        if self.playlist.is_empty() {
+        if self.play_queued_track() { return; }
+        if self.playing_tracks().is_empty() {
             self.status = "Playlist is empty.".to_string();
             self.reset_playback_state();
             return;
         }
-        if self.is_shuffle {
-            println!("play_next_track (Shuffle ON): Playing random track.");
-            self.play_random_track();
+        if self.shuffle_mode.is_on() {
+            println!("play_next_track (Shuffle ON): Playing next track of the shuffled order.");
+            self.play_shuffled_track();
         } else {
-            let current_idx = self.current_track_index.unwrap_or(self.playlist.len());
+            let current_idx = self.current_track_index.unwrap_or(self.playing_tracks().len());
             let next_index = current_idx + 1;
-            if next_index < self.playlist.len() {
+            if next_index < self.playing_tracks().len() {
                 println!("play_next_track (Sequential): Playing next track at index {}", next_index);
                 self.play_track_at_index(next_index);
             } else {
@@ -241,13 +1051,28 @@ impl MusicPlayerApp {
     }
 
     fn play_previous_track(&mut self) {
-        if self.playlist.is_empty() {
+        if self.playing_tracks().is_empty() {
              self.status = "Playlist is empty.".to_string();
              return;
         }
+        // Saindo de uma faixa da fila: volta para a faixa da playlist ouvida antes dela
+        if let Some(idx) = self.current_track_index.filter(|_| self.queue_playing.is_some()) {
+            self.play_track_at_index(idx);
+            return;
+        }
+        // Shuffle: volta pelo histórico do que foi realmente ouvido
+        if self.shuffle_mode.is_on() {
+            while let Some(path) = self.shuffle_order.previous() {
+                if let Some(index) = self.playing_tracks().iter().position(|p| *p == path) {
+                    println!("play_previous_track (Shuffle ON): Going back to index {}", index);
+                    self.play_track_at_index(index);
+                    return;
+                }
+            }
+        }
         let prev_index = self.current_track_index
             .and_then(|idx| idx.checked_sub(1))
-            .unwrap_or_else(|| self.playlist.len() - 1);
+            .unwrap_or_else(|| self.playing_tracks().len() - 1);
         println!("play_previous_track: Playing track at index {}", prev_index);
         self.play_track_at_index(prev_index);
     }
@@ -256,12 +1081,17 @@ impl MusicPlayerApp {
         self.current_track_duration = None;
         self.playback_start_time = None;
         self.elapsed_duration_at_pause = Duration::ZERO;
+        // A thread de áudio desliga o laço sozinha ao carregar outra faixa
+        self.ab_loop = AbLoop::default();
+        self.resume_pending = None;
     }
 
     fn reset_playback_state(&mut self) {
+        self.finish_listen(false);
         self.is_playing = false;
         self.is_paused = false;
         self.current_track_index = None;
+        self.queue_playing = None;
         self.reset_progress_state();
         self.is_loading = false;
         self.loading_file_path = None;
@@ -269,13 +1099,615 @@ impl MusicPlayerApp {
     }
 
     fn calculate_elapsed(&self) -> Duration {
+        // No laço A-B a thread de áudio volta para A sozinha: vale a posição publicada por ela
+        if self.ab_loop.region().is_some() { return self.playback_clock.position(); }
         if self.is_playing {
-            self.elapsed_duration_at_pause + self.playback_start_time.map_or(Duration::ZERO, |start| start.elapsed())
+            // O relógio da parede anda no andamento escolhido
+            self.elapsed_duration_at_pause + self.playback_start_time.map_or(Duration::ZERO, |start| self.track_time_since(start))
         } else {
             self.elapsed_duration_at_pause
         }
     }
 
+    // Índices reais da playlist que casam com a busca, na ordem da playlist.
+    // Os índices retornados continuam válidos para play_track_at_index e selected_track_index.
+    fn filtered_playlist_indices(&mut self) -> Vec<usize> {
+        let query = self.search_query.trim().to_string();
+        let tab = &self.playlists[self.active_playlist];
+        let (rating_filter, loved_filter) = (self.rating_filter, self.loved_filter);
+        let library = &self.library;
+        let passes_rating = |path: &Path| library.rating(path) >= rating_filter && (!loved_filter || library.is_loved(path));
+        if query.is_empty() {
+            return (0..tab.tracks.len()).filter(|&i| passes_rating(&tab.tracks[i])).collect();
+        }
+        for path in &tab.tracks {
+            self.metadata.request(path);
+        }
+        let mut matches = Vec::new();
+        for (index, path) in tab.tracks.iter().enumerate() {
+            if !passes_rating(path) { continue; }
+            let filename = self.get_filename(path);
+            let info = self.metadata.get(path);
+            let mut fields: Vec<&str> = vec![filename.as_str()];
+            if let Some(info) = info {
+                fields.extend([info.title.as_deref(), info.artist.as_deref(), info.album.as_deref()].into_iter().flatten());
+            }
+            if search::matches_all_terms(&query, &fields).is_some() {
+                matches.push(index);
+            }
+        }
+        matches
+    }
+
+    // --- Ordenação da playlist ---
+    // Reordena a aba exibida segundo `order` (order[novo] == antigo), mantendo
+    // current_track_index e a seleção apontando para as mesmas faixas.
+    fn apply_playlist_order(&mut self, order: &[usize]) {
+        if self.active_is_playing() {
+            self.current_track_index = playlist_table::remap_index(order, self.current_track_index);
+        }
+        self.active_tab_mut().apply_order(order);
+    }
+
+    // Move as faixas para antes de `insert_at` (índice real da playlist)
+    fn move_tracks(&mut self, moving: &[usize], insert_at: usize) {
+        if moving.is_empty() || self.smart_tab_is_read_only() { return; }
+        let order = playlist_table::move_order(self.active_tab().tracks.len(), moving, insert_at);
+        self.reorder_tracks(&order, moving.len());
+    }
+
+    // Sobe/desce a seleção uma posição, mantendo os espaços entre faixas não contíguas
+    fn shift_tracks(&mut self, moving: &[usize], up: bool) {
+        if moving.is_empty() || self.smart_tab_is_read_only() { return; }
+        let order = playlist_table::shift_order(self.active_tab().tracks.len(), moving, up);
+        self.reorder_tracks(&order, moving.len());
+    }
+
+    fn reorder_tracks(&mut self, order: &[usize], moved: usize) {
+        if order.iter().enumerate().all(|(new, &old)| new == old) { return; }
+        self.apply_playlist_order(order);
+        // A ordem manual invalida o indicador de ordenação do cabeçalho
+        self.active_tab_mut().sort = None;
+        self.status = format!("Moved {} track(s).", moved);
+    }
+
+    // Insere faixas na posição `at`, ajustando os índices que vêm depois dela.
+    // false se nada entrou (lista vazia ou aba inteligente, que já deixa o aviso no status).
+    fn insert_tracks(&mut self, at: usize, paths: Vec<PathBuf>) -> bool {
+        if paths.is_empty() || self.smart_tab_is_read_only() { return false; }
+        let at = at.min(self.active_tab().tracks.len());
+        let count = paths.len();
+        if self.active_is_playing() {
+            self.current_track_index = self.current_track_index.map(|i| if i >= at { i + count } else { i });
+        }
+        self.active_tab_mut().insert(at, paths);
+        true
+    }
+
+    // Arquivos e pastas soltos na janela a partir do gerenciador de arquivos
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect());
+        if dropped.is_empty() { return; }
+        // Aba inteligente não aceita faixas; nem vale a pena varrer as pastas
+        if self.smart_tab_is_read_only() { return; }
+
+        let files = files::collect_audio_files(&dropped);
+        if files.is_empty() {
+            self.status = "No supported audio files in the dropped items.".to_string();
+            return;
+        }
+
+        // Posição de inserção: antes/depois da linha sob o cursor; senão, no fim
+        let pointer = ctx.input(|i| i.pointer.latest_pos());
+        let insert_at = pointer
+            .and_then(|pos| self.playlist_row_rects.iter().find(|(_, rect)| rect.contains(pos)).map(|(index, rect)| (pos, *index, *rect)))
+            .map(|(pos, index, rect)| if pos.y > rect.center().y { index + 1 } else { index })
+            .unwrap_or(self.active_tab().tracks.len());
+
+        let num_added = files.len();
+        if !self.insert_tracks(insert_at, files) { return; }
+        self.status = format!("Added {} file(s) to playlist.", num_added);
+        self.error_message = None;
+    }
+
+    // Remove várias faixas de uma vez, corrigindo os índices da faixa atual e da seleção
+    fn remove_tracks(&mut self, indices: &[usize]) {
+        if self.smart_tab_is_read_only() { return; }
+        let track_count = self.active_tab().tracks.len();
+        let mut to_remove: Vec<usize> = indices.iter().copied().filter(|&i| i < track_count).collect();
+        to_remove.sort_unstable();
+        to_remove.dedup();
+        let Some(&first_removed) = to_remove.first() else { return; };
+
+        let current_in_active = self.current_index_in_active();
+        let was_playing_removed = self.playing_row_in_active().is_some_and(|idx| to_remove.binary_search(&idx).is_ok());
+        if was_playing_removed { self.send_audio_command(AudioCommand::Stop); }
+
+        let removed_name = self.get_filename(&self.active_tab().tracks[first_removed]);
+        self.active_tab_mut().remove_sorted(&to_remove);
+        self.status = if to_remove.len() == 1 { format!("Removed: {}", removed_name) } else { format!("Removed {} tracks.", to_remove.len()) };
+
+        if let Some(current_idx) = current_in_active {
+            self.current_track_index = if to_remove.binary_search(&current_idx).is_ok() { None }
+            else { Some(current_idx - to_remove.iter().filter(|&&i| i < current_idx).count()) };
+        }
+        if was_playing_removed { self.reset_playback_state(); }
+    }
+
+    fn apply_playlist_action(&mut self, ctx: &egui::Context, action: PlaylistAction) {
+        let selection = self.active_tab().selected_indices_sorted();
+        if selection.is_empty() { return; }
+        let track_count = self.active_tab().tracks.len();
+        match action {
+            PlaylistAction::MoveToTop => self.move_tracks(&selection, 0),
+            PlaylistAction::MoveToBottom => self.move_tracks(&selection, track_count),
+            PlaylistAction::MoveUp => self.shift_tracks(&selection, true),
+            PlaylistAction::MoveDown => self.shift_tracks(&selection, false),
+            PlaylistAction::PlayNext => self.queue_selection(&selection, true),
+            PlaylistAction::AddToQueue => self.queue_selection(&selection, false),
+            PlaylistAction::Remove => self.remove_tracks(&selection),
+            PlaylistAction::SetRating(rating) => {
+                let paths: Vec<PathBuf> = selection.iter().filter_map(|&i| self.active_tab().tracks.get(i).cloned()).collect();
+                self.set_rating(&paths, rating);
+            }
+            PlaylistAction::EditTags => {
+                let paths: Vec<PathBuf> = selection.iter()
+                    .filter_map(|&i| self.active_tab().tracks.get(i).cloned())
+                    .filter(|path| cue::split_track_path(path).is_none())
+                    .collect();
+                if paths.is_empty() { self.status = "CUE sheet tracks have no tags of their own to edit.".to_string(); }
+                else { self.tag_editor = Some(TagEditor::open(paths)); }
+            }
+            PlaylistAction::Organize => {
+                let paths: Vec<PathBuf> = selection.iter()
+                    .filter_map(|&i| self.active_tab().tracks.get(i).cloned())
+                    .filter(|path| cue::split_track_path(path).is_none())
+                    .collect();
+                self.open_organizer(paths);
+            }
+            PlaylistAction::ToggleLoved => {
+                let paths: Vec<PathBuf> = selection.iter().filter_map(|&i| self.active_tab().tracks.get(i).cloned()).collect();
+                let loved = !paths.iter().all(|path| self.library.is_loved(path));
+                self.set_loved(&paths, loved);
+            }
+            PlaylistAction::CopyPaths => {
+                let text = selection.iter()
+                    .filter_map(|&i| self.active_tab().tracks.get(i))
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ctx.output_mut(|o| o.copied_text = text);
+                self.status = format!("Copied {} path(s) to clipboard.", selection.len());
+            }
+            PlaylistAction::ShowInFileManager => {
+                let paths: Vec<PathBuf> = selection.iter().filter_map(|&i| self.active_tab().tracks.get(i).cloned()).collect();
+                if let Err(e) = files::reveal_in_file_manager(&paths) {
+                    self.error_message = Some(format!("Could not open file manager: {}", e));
+                }
+            }
+        }
+    }
+
+    // Ordena com as tags já lidas; as que faltam são pedidas em segundo plano e a aba é
+    // reordenada quando chegarem (ver resort_when_loaded)
+    fn sort_playlist(&mut self, column: PlaylistColumn, ascending: bool) {
+        let paths = self.active_tab().tracks.clone();
+        if column.needs_tags() {
+            for path in &paths { self.metadata.request(path); }
+            let missing = paths.iter().any(|path| self.metadata.get(path).is_none());
+            self.resort_pending = if missing { Some(self.active_tab().id) } else { None };
+        }
+        let filenames: Vec<String> = paths.iter().map(|p| self.get_filename(p)).collect();
+        let mut order: Vec<usize> = (0..paths.len()).collect();
+        order.sort_by(|&a, &b| {
+            let entry = |i: usize| SortEntry { index: i, path: &paths[i], filename: &filenames[i], info: self.metadata.get(&paths[i]), stats: self.library.stats(&paths[i]) };
+            let ordering = column.compare(entry(a), entry(b));
+            if ascending { ordering } else { ordering.reverse() }
+        });
+        self.apply_playlist_order(&order);
+        self.active_tab_mut().sort = Some(SortState { column, ascending });
+        self.status = format!("Playlist sorted by {} ({})", column.label(), if ascending { "ascending" } else { "descending" });
+    }
+
+    // Reaplica a ordenação pendente quando o leitor de tags termina, se a aba continua
+    // exibida e ordenada pela mesma coluna (uma ordem manual no meio do caminho cancela)
+    fn resort_when_loaded(&mut self) {
+        if self.metadata.is_loading() { return; }
+        let Some(tab_id) = self.resort_pending.take() else { return; };
+        if self.active_tab().id != tab_id { return; }
+        if let Some(SortState { column, ascending }) = self.active_tab().sort {
+            self.sort_playlist(column, ascending);
+        }
+    }
+
+    // --- Tabela da Playlist ---
+    // Desenha as linhas `visible_indices` (índices reais da playlist).
+    // Retorna o índice clicado que deve começar a tocar.
+    fn show_playlist_table(&mut self, ui: &mut egui::Ui, visible_indices: &[usize]) -> Option<usize> {
+        let mut play_clicked_index: Option<usize> = None;
+        let mut sort_request: Option<(PlaylistColumn, bool)> = None;
+        let mut columns_changed: Option<Vec<PlaylistColumn>> = None;
+        let mut drop_request: Option<(Vec<usize>, usize)> = None;
+        let mut playlist_action: Option<PlaylistAction> = None;
+        let mut rating_change: Option<(PathBuf, u8)> = None;
+        let mut loved_change: Option<(PathBuf, bool)> = None;
+        self.playlist_row_rects.clear();
+
+        let columns = self.playlist_columns.clone();
+        let current_track_idx_display = self.playing_row_in_active();
+        let tab_id = self.active_tab().id;
+        let playlist_sort = self.active_tab().sort;
+        let is_playing_display = self.is_playing;
+        let is_paused_display = self.is_paused;
+        let row_height = egui::TextStyle::Body.resolve(ui.style()).size + 6.0;
+        let max_height = ui.available_height() * 0.5;
+
+        egui::ScrollArea::horizontal().id_source(("playlist_table_hscroll", tab_id)).show(ui, |ui| {
+            // A ordem das colunas faz parte do id para que as larguras salvas acompanhem cada coluna;
+            // o id da aba mantém a rolagem de cada playlist separada
+            ui.push_id((tab_id, &columns), |ui| {
+                let mut table = egui_extras::TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(true)
+                    .sense(egui::Sense::click_and_drag())
+                    .auto_shrink([false, true])
+                    .max_scroll_height(max_height)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+                for column in &columns {
+                    table = table.column(egui_extras::Column::initial(column.initial_width()).at_least(24.0).clip(true));
+                }
+                table
+                    .header(row_height, |mut header| {
+                        for (position, column) in columns.iter().enumerate() {
+                            header.col(|ui| {
+                                let arrow = match playlist_sort {
+                                    Some(sort) if sort.column == *column => if sort.ascending { " ⏶" } else { " ⏷" },
+                                    _ => "",
+                                };
+                                let header_button = ui.add(egui::Button::new(egui::RichText::new(format!("{}{}", column.label(), arrow)).strong()).frame(false));
+                                if header_button.clicked() {
+                                    let ascending = !matches!(playlist_sort, Some(sort) if sort.column == *column && sort.ascending);
+                                    sort_request = Some((*column, ascending));
+                                }
+                                header_button.on_hover_text("Click to sort, right-click for column options").context_menu(|ui| {
+                                    if ui.add_enabled(position > 0, egui::Button::new("⏴ Move left")).clicked() {
+                                        let mut new_columns = columns.clone();
+                                        new_columns.swap(position, position - 1);
+                                        columns_changed = Some(new_columns);
+                                        ui.close_menu();
+                                    }
+                                    if ui.add_enabled(position + 1 < columns.len(), egui::Button::new("Move right ⏵")).clicked() {
+                                        let mut new_columns = columns.clone();
+                                        new_columns.swap(position, position + 1);
+                                        columns_changed = Some(new_columns);
+                                        ui.close_menu();
+                                    }
+                                    ui.separator();
+                                    for candidate in PlaylistColumn::ALL {
+                                        let mut visible = columns.contains(&candidate);
+                                        // Pelo menos uma coluna precisa continuar visível
+                                        let can_toggle = !(visible && columns.len() == 1);
+                                        if ui.add_enabled(can_toggle, egui::Checkbox::new(&mut visible, candidate.label())).changed() {
+                                            let mut new_columns = columns.clone();
+                                            if visible { new_columns.push(candidate); } else { new_columns.retain(|c| *c != candidate); }
+                                            columns_changed = Some(new_columns);
+                                        }
+                                    }
+                                    ui.separator();
+                                    if ui.button("Reset columns").clicked() {
+                                        columns_changed = Some(PlaylistColumn::ALL.to_vec());
+                                        ui.close_menu();
+                                    }
+                                });
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(row_height, visible_indices.len(), |mut row| {
+                            let index = visible_indices[row.index()];
+                            let path = self.active_tab().tracks[index].clone();
+                            self.metadata.request(&path);
+                            let filename = self.get_filename(&path);
+                            let info = self.metadata.get(&path);
+                            let stats = self.library.stats(&path);
+                            let is_current_track = current_track_idx_display == Some(index);
+                            row.set_selected(self.active_tab().is_selected(index));
+                            for column in &columns {
+                                row.col(|ui| {
+                                    // Avaliação e "loved" são editáveis direto na linha
+                                    match column {
+                                        PlaylistColumn::Rating => {
+                                            let current = stats.map_or(0, |s| s.rating);
+                                            if let Some(new_rating) = rating::stars(ui, current) { rating_change = Some((path.clone(), new_rating)); }
+                                            return;
+                                        }
+                                        PlaylistColumn::Loved => {
+                                            let current = stats.is_some_and(|s| s.loved);
+                                            if let Some(loved) = rating::loved(ui, current) { loved_change = Some((path.clone(), loved)); }
+                                            return;
+                                        }
+                                        _ => {}
+                                    }
+                                    let mut text = column.cell_text(index, &path, &filename, info, stats);
+                                    if *column == PlaylistColumn::Number {
+                                        if is_current_track && is_playing_display { text = format!("▶ {}", text); }
+                                        else if is_current_track && is_paused_display { text = format!("⏸ {}", text); }
+                                    }
+                                    let label = if is_current_track { egui::RichText::new(text).strong() } else { egui::RichText::new(text) };
+                                    ui.add(egui::Label::new(label).truncate(true).selectable(false));
+                                });
+                            }
+                            let response = row.response();
+                            self.playlist_row_rects.push((index, response.rect));
+                            if response.clicked() {
+                                let modifiers = ui_modifiers(&response);
+                                if modifiers.shift || modifiers.command {
+                                    self.active_tab_mut().handle_selection_click(index, modifiers.shift, modifiers.command, visible_indices);
+                                } else {
+                                    self.active_tab_mut().select_single(Some(index));
+                                    let should_play = match (current_track_idx_display, is_playing_display, is_paused_display) {
+                                        (Some(current_idx), true, false) if current_idx == index => false,
+                                        (Some(current_idx), false, true) if current_idx == index => true,
+                                        _ => true,
+                                    };
+                                    if should_play { play_clicked_index = Some(index); }
+                                }
+                            }
+
+                            // --- Arrastar e soltar para reordenar ---
+                            if response.drag_started() {
+                                let tab = self.active_tab_mut();
+                                let dragged = if tab.selected_indices.contains(&index) { tab.selected_indices_sorted() } else { vec![index] };
+                                if !tab.selected_indices.contains(&index) { tab.select_single(Some(index)); }
+                                response.dnd_set_drag_payload(PlaylistDragPayload(dragged));
+                            }
+                            let drop_after = response.ctx.pointer_interact_pos().is_some_and(|pos| pos.y > response.rect.center().y);
+                            if response.dnd_hover_payload::<PlaylistDragPayload>().is_some() {
+                                let y = if drop_after { response.rect.bottom() } else { response.rect.top() };
+                                let stroke = egui::Stroke::new(2.0, response.ctx.style().visuals.selection.stroke.color);
+                                response.ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("playlist_drop_marker")))
+                                    .hline(response.rect.x_range(), y, stroke);
+                            }
+                            if let Some(payload) = response.dnd_release_payload::<PlaylistDragPayload>() {
+                                let insert_at = if drop_after { index + 1 } else { index };
+                                drop_request = Some((payload.0.clone(), insert_at));
+                            }
+
+                            response.clone().context_menu(|ui| {
+                                let tab = self.active_tab_mut();
+                                if !tab.selected_indices.contains(&index) { tab.select_single(Some(index)); }
+                                let count = tab.selected_indices.len().max(1);
+                                ui.weak(format!("{} track(s) selected", count));
+                                ui.separator();
+                                let mut item = |ui: &mut egui::Ui, text: &str, action: PlaylistAction| {
+                                    if ui.button(text).clicked() { playlist_action = Some(action); ui.close_menu(); }
+                                };
+                                item(ui, "⏭ Play next", PlaylistAction::PlayNext);
+                                item(ui, "➕ Add to queue", PlaylistAction::AddToQueue);
+                                ui.separator();
+                                ui.menu_button("★ Rating", |ui| {
+                                    for value in (0..=rating::MAX_RATING).rev() {
+                                        let text = if value == 0 { "No rating".to_string() } else { rating::stars_text(value) };
+                                        item(ui, &text, PlaylistAction::SetRating(value));
+                                    }
+                                });
+                                item(ui, "♥ Love / unlove", PlaylistAction::ToggleLoved);
+                                item(ui, "🏷 Edit tags...", PlaylistAction::EditTags);
+                                item(ui, "📁 Organize files...", PlaylistAction::Organize);
+                                ui.separator();
+                                item(ui, "⏶ Move up", PlaylistAction::MoveUp);
+                                item(ui, "⏷ Move down", PlaylistAction::MoveDown);
+                                item(ui, "⏫ Move to top", PlaylistAction::MoveToTop);
+                                item(ui, "⏬ Move to bottom", PlaylistAction::MoveToBottom);
+                                ui.separator();
+                                item(ui, "📋 Copy path(s)", PlaylistAction::CopyPaths);
+                                item(ui, "📂 Show in file manager", PlaylistAction::ShowInFileManager);
+                                ui.separator();
+                                item(ui, "➖ Remove", PlaylistAction::Remove);
+                            });
+                            response.on_hover_text(path.display().to_string());
+                        });
+                    });
+            });
+        });
+
+        // Indicação do que está sendo arrastado, perto do cursor
+        if let Some(payload) = egui::DragAndDrop::payload::<PlaylistDragPayload>(ui.ctx()) {
+            egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("playlist_drag_tooltip"), |ui| {
+                ui.label(format!("Moving {} track(s)", payload.0.len()));
+            });
+        }
+
+        if let Some(new_columns) = columns_changed {
+            self.playlist_columns = new_columns;
+        }
+        // Clique nas estrelas/coração edita a faixa, não troca a música
+        if let Some((path, new_rating)) = rating_change {
+            self.set_rating(&[path], new_rating);
+            play_clicked_index = None;
+        }
+        if let Some((path, loved)) = loved_change {
+            self.set_loved(&[path], loved);
+            play_clicked_index = None;
+        }
+        if let Some((moving, insert_at)) = drop_request {
+            self.move_tracks(&moving, insert_at);
+        }
+        if let Some(action) = playlist_action {
+            self.apply_playlist_action(ui.ctx(), action);
+        }
+        if let Some((column, ascending)) = sort_request {
+            self.sort_playlist(column, ascending);
+        }
+        play_clicked_index
+    }
+
+    // Nome exibido na fila: "Artista - Título" das tags ou o nome do arquivo
+    fn track_label(&mut self, path: &Path) -> String {
+        track_label(&mut self.metadata, path)
+    }
+
+    // --- Painel da fila de reprodução ---
+    fn show_queue_panel(&mut self, ctx: &egui::Context) {
+        let queued = self.play_queue.tracks().to_vec();
+        let names: Vec<String> = queued.iter().map(|path| self.track_label(path)).collect();
+        let now_playing = self.queue_playing.clone().map(|path| self.track_label(&path));
+        let mut move_request: Option<(usize, usize)> = None;
+        let mut remove_request: Option<usize> = None;
+        let mut play_request: Option<usize> = None;
+        let mut clear_request = false;
+
+        egui::SidePanel::right("queue_panel").resizable(true).default_width(230.0).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Queue");
+                if ui.add_enabled(!names.is_empty(), egui::Button::new("🗑️ Clear").small()).clicked() { clear_request = true; }
+            });
+            if let Some(name) = &now_playing {
+                ui.label(format!("▶ {}", name));
+            }
+            ui.separator();
+            if names.is_empty() {
+                ui.weak("The queue is empty. Use \"Play next\" or \"Add to queue\" on playlist tracks.");
+                return;
+            }
+            egui::ScrollArea::vertical().id_source("queue_scroll").show(ui, |ui| {
+                for (index, name) in names.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(index > 0, egui::Button::new("⏶").small()).on_hover_text("Move up").clicked() {
+                            move_request = Some((index, index - 1));
+                        }
+                        if ui.add_enabled(index + 1 < names.len(), egui::Button::new("⏷").small()).on_hover_text("Move down").clicked() {
+                            move_request = Some((index, index + 1));
+                        }
+                        if ui.small_button("✖").on_hover_text("Remove from queue").clicked() { remove_request = Some(index); }
+                        let label = ui.add(egui::Label::new(format!("{}. {}", index + 1, name)).truncate(true).sense(egui::Sense::click()));
+                        if label.on_hover_text("Double-click to play now").double_clicked() { play_request = Some(index); }
+                    });
+                }
+            });
+        });
+
+        if clear_request {
+            self.play_queue.clear();
+            self.status = "Queue cleared.".to_string();
+        }
+        if let Some((index, to)) = move_request { self.play_queue.move_entry(index, to); }
+        if let Some(index) = remove_request { self.play_queue.remove(index); }
+        if let Some(index) = play_request {
+            self.play_queue.move_entry(index, 0);
+            self.play_queued_track();
+        }
+    }
+
+    // --- Painel de letras ---
+    fn show_lyrics_panel(&mut self, ctx: &egui::Context) {
+        let playing = if self.is_playing || self.is_paused { self.now_playing_path() } else { None };
+        self.lyrics_view.set_track(playing.as_deref());
+        // Posição publicada pela thread de áudio, não a estimativa pelo relógio
+        let position = self.playback_clock.position();
+        let mut seek_request = None;
+        egui::SidePanel::right("lyrics_panel").resizable(true).default_width(260.0).show(ctx, |ui| {
+            ui.heading("Lyrics");
+            seek_request = self.lyrics_view.show(ui, position);
+        });
+        if let Some(position) = seek_request { self.seek_to(position); }
+    }
+
+    // --- Visualizador ---
+    fn show_visualizer_panel(&mut self, ctx: &egui::Context) {
+        let visualizer = self.visualizer.get_or_insert_with(Visualizer::default);
+        egui::TopBottomPanel::bottom("visualizer_panel").resizable(true).default_height(140.0).min_height(80.0).show(ctx, |ui| {
+            visualizer.show(ui, &self.sample_tap, &mut self.spectrum_style);
+        });
+        if self.is_playing { ctx.request_repaint(); }
+    }
+
+    // Tempo de faixa tocado desde `start` (relógio da parede × velocidade)
+    fn track_time_since(&self, start: Instant) -> Duration {
+        start.elapsed().mul_f32(self.playback_speed.rate)
+    }
+
+    // Fixa o tempo decorrido antes de trocar a base do cálculo (laço ou velocidade)
+    fn rebase_elapsed(&mut self) {
+        self.elapsed_duration_at_pause = self.calculate_elapsed();
+        if self.playback_start_time.is_some() { self.playback_start_time = Some(Instant::now()); }
+    }
+
+    // --- Retomada e marcadores ---
+    // Guarda onde a faixa atual parou (só arquivos longos); tocada até o fim, a posição é esquecida
+    fn remember_resume_position(&mut self, completed: bool) {
+        if !self.resume_long_files { return; }
+        let (Some(listen), Some(duration)) = (&self.current_listen, self.current_track_duration) else { return; };
+        if duration < Duration::from_secs(self.resume_min_minutes as u64 * 60) { return; }
+        let path = listen.path.clone();
+        // Posição publicada pela thread de áudio (acompanha laço e velocidade)
+        let position = self.playback_clock.position();
+        let resume = (!completed && position >= RESUME_MIN_POSITION && position + RESUME_END_MARGIN < duration).then_some(position);
+        self.library.set_resume_position(&path, resume);
+    }
+
+    fn resume_track(&mut self, path: &Path) {
+        if !self.resume_long_files { return; }
+        let Some(position) = self.library.resume_position(path) else { return; };
+        println!("Resume: Continuing {:?} at {:?}", path, position);
+        self.seek_to(position);
+    }
+
+    fn apply_bookmark_action(&mut self, path: &Path, action: BookmarkAction) {
+        match action {
+            BookmarkAction::Add(bookmark) => {
+                self.status = format!("Bookmark \"{}\" added at {}.", bookmark.name, practice::format_position(bookmark.position));
+                self.library.add_bookmark(path, bookmark);
+            }
+            BookmarkAction::Jump(position) => self.seek_to(position),
+            BookmarkAction::Remove(index) => {
+                self.library.remove_bookmark(path, index);
+                self.status = "Bookmark deleted.".to_string();
+            }
+        }
+    }
+
+    // --- Velocidade e tom ---
+    fn send_playback_speed(&mut self) {
+        let speed = self.playback_speed;
+        self.send_audio_command(AudioCommand::SetSpeed(speed.rate));
+        self.send_audio_command(AudioCommand::SetPreservePitch(speed.preserve_pitch));
+        self.send_audio_command(AudioCommand::SetPitchShift(speed.semitones));
+    }
+
+    fn set_playback_speed(&mut self, speed: PlaybackSpeed) {
+        self.rebase_elapsed();
+        self.playback_speed = speed;
+        self.send_playback_speed();
+        self.status = speed.describe();
+    }
+
+    // --- Modo de prática ---
+    fn apply_practice_action(&mut self, action: PracticeAction) {
+        self.rebase_elapsed();
+        match action {
+            PracticeAction::SetStart(position) => self.ab_loop.set_start(position),
+            PracticeAction::SetEnd(position) => self.ab_loop.set_end(position),
+            PracticeAction::ClearLoop => self.ab_loop = AbLoop::default(),
+        }
+        let region = self.ab_loop.region();
+        self.send_audio_command(AudioCommand::SetLoop(region));
+        self.status = self.ab_loop.describe();
+        // Ligado fora do trecho, o laço começa em A
+        if let Some((start, end)) = region {
+            let position = self.elapsed_duration_at_pause;
+            if position < start || position >= end { self.seek_to(start); }
+        }
+    }
+
+    fn seek_to(&mut self, position: Duration) {
+        println!("GUI: Requesting Seek to: {:?}", position);
+        self.send_audio_command(AudioCommand::Seek(position));
+        self.elapsed_duration_at_pause = position;
+        if self.is_playing { self.playback_start_time = Some(Instant::now()); }
+        else { self.playback_start_time = None; }
+    }
+
     fn format_duration(duration: Duration) -> String {
         let total_secs = duration.as_secs();
         let mins = total_secs / 60;
@@ -283,6 +1715,103 @@ impl MusicPlayerApp {
         format!("{:02}:{:02}", mins, secs)
     }
 
+    // --- Janela do histórico de reprodução (mais recentes primeiro) ---
+    fn show_history_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_history_window;
+        let mut export_requested = false;
+        let exporting = self.history_export.is_some();
+        // Só as linhas visíveis são desenhadas (e só elas pedem tags); o mais recente primeiro
+        let records = &self.library.history;
+        let metadata = &mut self.metadata;
+        let completed = records.iter().filter(|record| record.completed).count();
+
+        egui::Window::new("Playback History")
+            .open(&mut open)
+            .default_size([560.0, 360.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} listens ({} completed, {} skipped)", records.len(), completed, records.len() - completed));
+                    let label = if exporting { "Exporting..." } else { "Export CSV..." };
+                    if ui.add_enabled(!records.is_empty() && !exporting, egui::Button::new(label)).clicked() { export_requested = true; }
+                });
+                ui.separator();
+                if records.is_empty() {
+                    ui.weak("Nothing played yet.");
+                    return;
+                }
+                let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+                egui_extras::TableBuilder::new(ui)
+                    .striped(true)
+                    .column(egui_extras::Column::initial(140.0).resizable(true))
+                    .column(egui_extras::Column::remainder().at_least(120.0).clip(true))
+                    .column(egui_extras::Column::initial(60.0))
+                    .column(egui_extras::Column::initial(70.0))
+                    .header(row_height, |mut header| {
+                        for title in ["Date", "Track", "Played", "Result"] {
+                            header.col(|ui| { ui.strong(title); });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(row_height, records.len(), |mut row| {
+                            let record = &records[records.len() - 1 - row.index()];
+                            let label = track_label(metadata, &record.path);
+                            let date = record.started_at_local().map(|t| t.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+                            row.col(|ui| { ui.label(date); });
+                            row.col(|ui| { ui.add(egui::Label::new(label).truncate(true)).on_hover_text(record.path.display().to_string()); });
+                            row.col(|ui| { ui.label(Self::format_duration(record.played)); });
+                            row.col(|ui| { ui.label(if record.completed { "Completed" } else { "Skipped" }); });
+                        });
+                    });
+            });
+
+        self.show_history_window = open;
+        if export_requested { self.export_history_csv(); }
+    }
+
+    fn open_scrobble_folder(&mut self) {
+        let Some(dir) = scrobble::log_dir() else {
+            self.error_message = Some("No data directory available".to_string());
+            return;
+        };
+        let result = std::fs::create_dir_all(&dir).and_then(|_| files::open_folder(&dir));
+        if let Err(e) = result { self.error_message = Some(format!("Failed to open {}: {}", dir.display(), e)); }
+    }
+
+    // Roda numa thread: as tags que ainda não estão no cache são lidas lá, uma vez por faixa
+    fn export_history_csv(&mut self) {
+        let Some(path) = FileDialog::new().add_filter("CSV", &["csv"]).set_file_name("history.csv").save_file() else { return; };
+        let history = self.library.history.clone();
+        let mut tags: HashMap<PathBuf, TrackInfo> = history.iter()
+            .filter_map(|record| self.metadata.get(&record.path).map(|info| (record.path.clone(), info.clone())))
+            .collect();
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            let csv = Library::history_csv(&history, |track| {
+                let info = tags.entry(track.to_path_buf()).or_insert_with(|| TrackInfo::read(track));
+                (info.artist.clone().unwrap_or_default(), info.title.clone().unwrap_or_default(), info.album.clone().unwrap_or_default())
+            });
+            let result = match std::fs::write(&path, csv) {
+                Ok(()) => Ok(format!("Exported {} listen(s) to '{}'.", history.len(), display_name(&path))),
+                Err(e) => Err(format!("Failed to export history: {}", e)),
+            };
+            let _ = sender.send(result);
+        });
+        self.history_export = Some(receiver);
+        self.status = "Exporting history...".to_string();
+    }
+
+    fn poll_history_export(&mut self) {
+        let Some(receiver) = &self.history_export else { return; };
+        match receiver.try_recv() {
+            Ok(Ok(status)) => self.status = status,
+            Ok(Err(e)) => self.error_message = Some(e),
+            Err(CrossbeamTryRecvError::Empty) => return,
+            Err(CrossbeamTryRecvError::Disconnected) => {}
+        }
+        self.history_export = None;
+    }
+
     // --- Função para mostrar a janela "Sobre" ---
     fn show_about_window(&mut self, ctx: &egui::Context) {
         // Cria uma nova janela egui
@@ -323,13 +1852,57 @@ impl MusicPlayerApp {
     }
 }
 
+// Pasta mais profunda que contém todos os arquivos
+fn common_parent(paths: &[PathBuf]) -> PathBuf {
+    let mut common = paths.first().and_then(|path| path.parent()).map(Path::to_path_buf).unwrap_or_default();
+    for path in paths.iter().skip(1) {
+        while !path.starts_with(&common) {
+            if !common.pop() { break; }
+        }
+    }
+    common
+}
+
+// Modificadores de teclado no momento do clique
+fn ui_modifiers(response: &egui::Response) -> egui::Modifiers {
+    response.ctx.input(|i| i.modifiers)
+}
+
+// Nome do arquivo; faixa de CUE sheet: "Álbum.cue #03"
+fn display_name(path: &Path) -> String {
+    if let Some((cue_path, number)) = cue::split_track_path(path) {
+        return format!("{} #{:02}", display_name(cue_path), number);
+    }
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+// "Artista - Título" das tags (pedidas se faltarem) ou, até chegarem, o nome do arquivo
+fn track_label(metadata: &mut MetadataCache, path: &Path) -> String {
+    metadata.request(path);
+    match metadata.get(path).map(|info| (&info.artist, &info.title)) {
+        Some((Some(artist), Some(title))) => format!("{} - {}", artist, title),
+        Some((None, Some(title))) => title.clone(),
+        _ => display_name(path),
+    }
+}
+
 // --- Implementação eframe::App ---
 impl eframe::App for MusicPlayerApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, eframe::APP_KEY, self);
+        // Também no salvamento automático: fechar o app no meio de um audiolivro não perde a posição
+        self.remember_resume_position(false);
+        if let Err(e) = self.library.save() { eprintln!("Library: Failed to save: {}", e); }
         println!("App state saved.");
     }
 
+    // Gravações de tags adiadas (faixa tocando) ou em andamento não se perdem ao fechar
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.flush_tag_writes();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // --- Processar Respostas de Áudio ---
         let mut responses_to_process = Vec::new();
@@ -362,7 +1935,9 @@ impl eframe::App for MusicPlayerApp {
                     }
                     self.error_message = Some(format!("Failed to load '{}': {}", path.display(), err_msg));
                     self.status = "Error loading file".to_string();
-                    if self.current_track_index.map_or(false, |idx| self.playlist.get(idx) == Some(&path)) {
+                    let was_current = self.queue_playing.as_ref() == Some(&path)
+                        || self.current_track_index.is_some_and(|idx| self.playing_tracks().get(idx) == Some(&path));
+                    if was_current {
                         self.reset_playback_state();
                     }
                 }
@@ -373,11 +1948,15 @@ impl eframe::App for MusicPlayerApp {
                     self.loading_file_path = None;
                     self.error_message = None;
                     self.playback_start_time = Some(Instant::now());
-                    if let Some(idx) = self.current_track_index {
-                        if let Some(path) = self.playlist.get(idx) {
+                    if let Some(path) = &self.queue_playing {
+                        self.status = format!("Playing: {}", self.get_filename(path));
+                    } else if let Some(idx) = self.current_track_index {
+                        if let Some(path) = self.playing_tracks().get(idx) {
                             self.status = format!("Playing: {}", self.get_filename(path));
                         }
                     } else { self.status = "Playing...".to_string(); }
+                    // Faixa recém-carregada: arquivo longo volta para onde parou
+                    if let Some(path) = self.resume_pending.take() { self.resume_track(&path); }
                 }
                 AudioResponse::PlaybackPaused => {
                     if self.is_playing {
@@ -386,7 +1965,7 @@ impl eframe::App for MusicPlayerApp {
                         self.status = "Paused".to_string();
                         self.error_message = None;
                         if let Some(start) = self.playback_start_time.take() {
-                            self.elapsed_duration_at_pause += start.elapsed();
+                            self.elapsed_duration_at_pause += self.track_time_since(start);
                         }
                     }
                 }
@@ -396,22 +1975,28 @@ impl eframe::App for MusicPlayerApp {
                 }
                 AudioResponse::PlaybackEnded => {
                     println!("GUI: Playback ended detected.");
-                    if let Some(last_played_index) = self.current_track_index {
+                    self.finish_listen(true);
+                    if let Some(path) = self.queue_playing.clone().filter(|_| self.repeat_mode == RepeatMode::Track) {
+                        println!("Repeat Track: Replaying queued track");
+                        self.play_path_from_queue(path);
+                    } else if self.play_queued_track() {
+                        println!("Queue: Playing next queued track");
+                    } else if let Some(last_played_index) = self.current_track_index {
                         match self.repeat_mode {
                             RepeatMode::Track => {
                                 println!("Repeat Track: Replaying index {}", last_played_index);
                                 self.play_track_at_index(last_played_index);
                             }
                             RepeatMode::Playlist => {
-                                println!("Repeat Playlist: Playing next track (shuffle={})", self.is_shuffle);
+                                println!("Repeat Playlist: Playing next track (shuffle={:?})", self.shuffle_mode);
                                 self.play_next_track();
                             }
                             RepeatMode::Off => {
-                                if self.is_shuffle {
-                                    println!("Repeat Off (Shuffle ON): Playing next random track");
-                                    self.play_random_track();
+                                if self.shuffle_mode.is_on() {
+                                    println!("Repeat Off (Shuffle ON): Playing next shuffled track");
+                                    self.play_next_track();
                                 } else {
-                                    if last_played_index >= self.playlist.len().saturating_sub(1) {
+                                    if last_played_index >= self.playing_tracks().len().saturating_sub(1) {
                                         println!("Repeat Off (Sequential): Playlist finished.");
                                         self.status = "Playlist finished.".to_string();
                                         self.reset_playback_state();
@@ -424,27 +2009,42 @@ impl eframe::App for MusicPlayerApp {
                         }
                     } else {
                         println!("PlaybackEnded received but no current_track_index known. Stopping.");
+                        if self.queue_playing.is_some() { self.status = "Queue finished.".to_string(); }
                         self.reset_playback_state();
                     }
                 }
-                AudioResponse::CurrentlyPlaying(index_option, duration_option) => {
-                    self.current_track_index = index_option;
+                AudioResponse::CurrentlyPlaying(path_option, index_option, duration_option) => {
                     self.current_track_duration = duration_option;
                     self.is_loading = false;
                     self.loading_file_path = None;
+                    if let (Some(path), None) = (path_option, index_option) {
+                        // Faixa da fila: a posição na playlist (current_track_index) não muda
+                        self.status = format!("Playing: {}", self.get_filename(&path));
+                        self.start_listen(path);
+                        self.error_message = None;
+                        self.is_playing = true;
+                        self.is_paused = false;
+                        self.elapsed_duration_at_pause = Duration::ZERO;
+                        self.playback_start_time = Some(Instant::now());
+                        continue;
+                    }
+                    self.current_track_index = index_option;
                     if let Some(idx) = index_option {
-                        if let Some(path) = self.playlist.get(idx) {
-                            self.status = format!("Playing: {}", self.get_filename(path));
+                        if let Some(path) = self.playing_tracks().get(idx).cloned() {
+                            self.status = format!("Playing: {}", self.get_filename(&path));
+                            self.shuffle_order.record(self.playlists[self.playing_playlist].id, &path);
+                            self.start_listen(path);
                             self.error_message = None;
                             self.is_playing = true;
                             self.is_paused = false;
-                            self.selected_track_index = Some(idx);
+                            let playing_tab = &mut self.playlists[self.playing_playlist];
+                            if !playing_tab.selected_indices.contains(&idx) { playing_tab.select_single(Some(idx)); }
                             self.elapsed_duration_at_pause = Duration::ZERO;
                             self.playback_start_time = Some(Instant::now());
                         } else {
                             self.status = format!("Error: Playing unknown track at index {}", idx);
                             self.error_message = Some(format!("Playlist desync? Index {} not found.", idx));
-                            eprintln!("Playlist desync: Audio thread reported playing index {} but playlist has {} items.", idx, self.playlist.len());
+                            eprintln!("Playlist desync: Audio thread reported playing index {} but playlist has {} items.", idx, self.playing_tracks().len());
                             self.reset_playback_state();
                         }
                     } else {
@@ -456,6 +2056,7 @@ impl eframe::App for MusicPlayerApp {
                 }
                 AudioResponse::SeekCompleted(new_elapsed_time) => {
                     println!("GUI: Received SeekCompleted confirmation: {:?}", new_elapsed_time);
+                    // Posição na faixa; daqui em diante o relógio conta na velocidade atual (track_time_since)
                     self.elapsed_duration_at_pause = new_elapsed_time;
                     if self.is_playing { self.playback_start_time = Some(Instant::now()); }
                     else { self.playback_start_time = None; }
@@ -464,11 +2065,65 @@ impl eframe::App for MusicPlayerApp {
             }
         }
 
+        self.check_scrobble();
+
+        // --- Arquivos arrastados do gerenciador de arquivos ---
+        self.handle_dropped_files(ctx);
+        let hovering_files = ctx.input(|i| !i.raw.hovered_files.is_empty());
+
+        // --- Metadados lidos em segundo plano ---
+        if self.metadata.poll() || self.metadata.is_loading() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        self.resort_when_loaded();
+        self.poll_history_export();
+        if self.history_export.is_some() { ctx.request_repaint_after(Duration::from_millis(100)); }
+        self.refresh_smart_playlists(false);
+        self.apply_pending_tag_writes();
+        self.poll_tag_writes();
+        if self.tag_writes.is_busy() { ctx.request_repaint_after(Duration::from_millis(100)); }
+
         // --- Adicionar Menu Superior ---
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
-                // Removido o menu "Arquivo" como discutido
-                // ui.menu_button("Arquivo", |ui| { ... });
+                ui.menu_button("Arquivo", |ui| {
+                    if ui.button("Importar playlist...").clicked() {
+                        ui.close_menu();
+                        self.import_playlist();
+                    }
+                    if ui.add_enabled(!self.active_tab().tracks.is_empty(), egui::Button::new("Exportar playlist...")).clicked() {
+                        ui.close_menu();
+                        self.export_playlist();
+                    }
+                    ui.separator();
+                    if ui.button("Histórico de reprodução...").clicked() {
+                        self.show_history_window = true;
+                        ui.close_menu();
+                    }
+                    ui.checkbox(&mut self.write_rating_tags, "Gravar avaliações nas tags")
+                        .on_hover_text("Also write ratings to the files as FMPS_RATING / POPM tags");
+                    ui.checkbox(&mut self.scrobble_log_enabled, "Registrar scrobbles offline")
+                        .on_hover_text("Write listened tracks to .scrobbler.log and a ListenBrainz listens file");
+                    if ui.button("Abrir pasta dos scrobbles").clicked() {
+                        ui.close_menu();
+                        self.open_scrobble_folder();
+                    }
+                    ui.checkbox(&mut self.resume_long_files, "Retomar arquivos longos")
+                        .on_hover_text("Continue long files (audiobooks, lectures, DJ mixes) where they stopped");
+                    ui.add_enabled_ui(self.resume_long_files, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("A partir de");
+                            ui.add(egui::DragValue::new(&mut self.resume_min_minutes).clamp_range(1..=600).suffix(" min"));
+                        });
+                    });
+                    ui.separator();
+                    let undo_hint = UndoLog::last_batch().map(|batch| format!("Move {} file(s) back to where they were", batch.moves.len()));
+                    if ui.add_enabled(undo_hint.is_some(), egui::Button::new("Desfazer última organização"))
+                        .on_hover_text(undo_hint.unwrap_or_else(|| "Nothing to undo".to_string())).clicked() {
+                        ui.close_menu();
+                        self.undo_organize();
+                    }
+                });
 
                 ui.menu_button("Ajuda", |ui| {
                     if ui.button("Sobre...").clicked() {
@@ -484,56 +2139,136 @@ impl eframe::App for MusicPlayerApp {
             self.show_about_window(ctx); // Chama a função corrigida
         }
 
+        // --- Janela do Histórico de Reprodução ---
+        if self.show_history_window {
+            self.show_history_window(ctx);
+        }
+
+        // --- Editor de Playlist Inteligente ---
+        if self.smart_editor.is_some() {
+            self.show_smart_editor(ctx);
+        }
+
+        // --- Editor de Tags ---
+        if self.tag_editor.is_some() {
+            self.show_tag_editor(ctx);
+        }
+
+        // --- Organizador de Arquivos ---
+        if self.organize_window.is_some() {
+            self.show_organizer(ctx);
+        }
+
+        // --- Painel da Fila de Reprodução ---
+        self.sample_tap.set_enabled(self.show_visualizer && self.is_playing);
+        if self.show_visualizer {
+            self.show_visualizer_panel(ctx);
+        }
+        if self.show_lyrics_panel {
+            self.show_lyrics_panel(ctx);
+        }
+        if self.show_queue_panel {
+            self.show_queue_panel(ctx);
+        }
+
         // --- Layout da UI Principal (Revertido para o Original) ---
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Rust Music Player Alfa 1.1");
             ui.separator();
 
+            // --- Faixa atual: nome, avaliação e "loved" ---
+            if let Some(path) = self.now_playing_path() {
+                let label = self.track_label(&path);
+                let (current_rating, current_loved) = (self.library.rating(&path), self.library.is_loved(&path));
+                let mut rating_change = None;
+                let mut loved_change = None;
+                ui.horizontal(|ui| {
+                    ui.add(egui::Label::new(egui::RichText::new(format!("♪ {}", label)).strong()).truncate(true));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        loved_change = rating::loved(ui, current_loved);
+                        ui.add_space(6.0);
+                        // right_to_left desenha as estrelas invertidas; um sub-layout mantém a ordem
+                        ui.horizontal(|ui| { rating_change = rating::stars(ui, current_rating); });
+                    });
+                });
+                if let Some(new_rating) = rating_change { self.set_rating(std::slice::from_ref(&path), new_rating); }
+                if let Some(loved) = loved_change { self.set_loved(&[path], loved); }
+            }
+
             // --- Barra de Progresso/Seek e Tempos ---
             let elapsed = self.calculate_elapsed();
             let total = self.current_track_duration.unwrap_or(Duration::ZERO);
             let mut seek_to_fraction: Option<f32> = None;
+            let mut practice_action = None;
+            let mut bookmark_action = None;
+            let playing_path = if self.is_playing || self.is_paused { self.now_playing_path() } else { None };
+            self.waveform.request(playing_path.as_deref());
+            let peaks = self.waveform.poll();
+            if self.waveform.is_loading() { ctx.request_repaint_after(Duration::from_millis(250)); }
             ui.vertical(|ui| {
-                let slider_enabled = (self.is_playing || self.is_paused) && total > Duration::ZERO && self.audio_command_sender.is_some();
+                let seek_enabled = (self.is_playing || self.is_paused) && total > Duration::ZERO && self.audio_command_sender.is_some();
                 let current_progress = if total > Duration::ZERO { (elapsed.as_secs_f32() / total.as_secs_f32()).clamp(0.0, 1.0) } else { 0.0 };
-                let mut slider_value = current_progress;
-                let progress_slider = ui.add_enabled(
-                    slider_enabled,
-                    egui::Slider::new(&mut slider_value, 0.0..=1.0)
-                        .show_value(false)
-                        .min_decimals(3)
-                        .step_by(0.001)
-                );
-                if progress_slider.changed() { seek_to_fraction = Some(slider_value); }
-                progress_slider.on_hover_text(format!("{} / {} (Click or Drag to Seek)", Self::format_duration(elapsed), Self::format_duration(total)));
+                let fraction_of = |position: Duration| if total > Duration::ZERO { Some(position.as_secs_f32() / total.as_secs_f32()) } else { None };
+                let loop_marks = (self.ab_loop.start.and_then(fraction_of), self.ab_loop.end.and_then(fraction_of));
+                let bookmark_marks: Vec<f32> = playing_path.as_deref().map_or(&[][..], |path| self.library.bookmarks(path)).iter()
+                    .filter_map(|bookmark| fraction_of(bookmark.position)).collect();
+                let (waveform_response, seek) = waveform_bar::show(ui, peaks.as_deref(), current_progress, loop_marks, &bookmark_marks, seek_enabled);
+                seek_to_fraction = seek;
+                let pointer_position = |pos: egui::Pos2| total.mul_f32(((pos.x - waveform_response.rect.left()) / waveform_response.rect.width()).clamp(0.0, 1.0));
+                if waveform_response.secondary_clicked() {
+                    self.loop_menu_position = waveform_response.interact_pointer_pos().map(pointer_position);
+                }
+                if seek_enabled {
+                    waveform_response.context_menu(|ui| {
+                        let Some(position) = self.loop_menu_position else { ui.close_menu(); return; };
+                        ui.label(egui::RichText::new(practice::format_position(position)).weak());
+                        if ui.button("Set loop start (A) here").clicked() { practice_action = Some(PracticeAction::SetStart(position)); ui.close_menu(); }
+                        if ui.button("Set loop end (B) here").clicked() { practice_action = Some(PracticeAction::SetEnd(position)); ui.close_menu(); }
+                        if ui.add_enabled(self.ab_loop.start.is_some(), egui::Button::new("Clear loop")).clicked() { practice_action = Some(PracticeAction::ClearLoop); ui.close_menu(); }
+                        ui.separator();
+                        if ui.button("Add bookmark here").clicked() {
+                            let count = playing_path.as_deref().map_or(0, |path| self.library.bookmarks(path).len());
+                            bookmark_action = Some(BookmarkAction::Add(Bookmark { name: format!("Bookmark {}", count + 1), position }));
+                            ui.close_menu();
+                        }
+                    });
+                }
+                let hover = match waveform_response.hover_pos().filter(|_| seek_enabled) {
+                    Some(pos) => format!("Seek to {}", Self::format_duration(pointer_position(pos))),
+                    None => format!("{} / {}", Self::format_duration(elapsed), Self::format_duration(total)),
+                };
+                waveform_response.on_hover_text(hover);
+                let rate = self.playback_speed.rate;
                 ui.horizontal(|ui| {
                     ui.label(Self::format_duration(elapsed));
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.label(Self::format_duration(total));
+                        // Posição e duração são da faixa; fora de 1× mostra também quanto falta no relógio
+                        if rate != 1.0 && total > elapsed {
+                            let left = (total - elapsed).div_f32(rate);
+                            ui.label(egui::RichText::new(format!("−{} at {:.2}×", Self::format_duration(left), rate)).weak());
+                        }
                     });
                 });
             });
             ui.separator();
 
+            if let Some(action) = practice_action { self.apply_practice_action(action); }
+            if let (Some(action), Some(path)) = (bookmark_action, &playing_path) { self.apply_bookmark_action(path, action); }
             if let Some(fraction) = seek_to_fraction {
                 if total > Duration::ZERO {
                     let target_secs = total.as_secs_f64() * fraction as f64;
-                    let seek_duration = Duration::from_secs_f64(target_secs);
-                    println!("GUI: Requesting Seek to: {:?} (fraction: {})", seek_duration, fraction);
-                    self.send_audio_command(AudioCommand::Seek(seek_duration));
-                    self.elapsed_duration_at_pause = seek_duration;
-                    if self.is_playing { self.playback_start_time = Some(Instant::now()); }
-                    else { self.playback_start_time = None; }
+                    self.seek_to(Duration::from_secs_f64(target_secs));
                     ctx.request_repaint();
                 }
             }
 
             // --- Controles de Reprodução (Layout Original) ---
             ui.horizontal(|ui| {
-                 let can_interact_playback = !self.is_loading && !self.playlist.is_empty() && self.audio_command_sender.is_some();
+                 let can_interact_playback = !self.is_loading && (!self.active_tab().tracks.is_empty() || self.has_playback()) && self.audio_command_sender.is_some();
                  let can_stop = (self.is_playing || self.is_paused) && self.audio_command_sender.is_some();
-                 let can_previous = !self.is_loading && self.audio_command_sender.is_some() && !self.playlist.is_empty();
-                 let can_next = !self.is_loading && self.audio_command_sender.is_some() && !self.playlist.is_empty();
+                 let can_previous = !self.is_loading && self.audio_command_sender.is_some() && !self.playing_tracks().is_empty();
+                 let can_next = !self.is_loading && self.audio_command_sender.is_some() && (!self.playing_tracks().is_empty() || !self.play_queue.is_empty());
 
                  let play_pause_text = if self.is_playing { "Pause ❚❚" } else { "Play ▶" };
                  let play_pause_button = ui.add_enabled(can_interact_playback, egui::Button::new(play_pause_text).min_size(egui::vec2(60.0, 0.0)));
@@ -542,8 +2277,9 @@ impl eframe::App for MusicPlayerApp {
                      else {
                          if self.is_paused { self.send_audio_command(AudioCommand::Play); }
                          else {
-                             let index_to_play = self.selected_track_index.filter(|&idx| idx < self.playlist.len()).unwrap_or(0);
-                             if !self.playlist.is_empty() { self.play_track_at_index(index_to_play); }
+                             let tab = self.active_tab();
+                             let index_to_play = tab.selected_track_index.filter(|&idx| idx < tab.tracks.len()).unwrap_or(0);
+                             if !tab.tracks.is_empty() { self.play_track_in_active(index_to_play); }
                          }
                      }
                  }
@@ -557,13 +2293,23 @@ impl eframe::App for MusicPlayerApp {
                  let next_button = ui.add_enabled(can_next, egui::Button::new("Next ⏭"));
                  if next_button.clicked() { self.play_next_track(); }
 
-                 let shuffle_text = if self.is_shuffle { "🔀 ON" } else { "🔀 OFF" };
-                 let shuffle_button = ui.add_enabled(can_interact_playback, egui::Button::new(shuffle_text).selected(self.is_shuffle));
+                 // Clique alterna entre os modos; botão direito escolhe um modo direto
+                 let shuffle_button = ui.add_enabled(
+                     can_interact_playback,
+                     egui::Button::new(self.shuffle_mode.icon()).selected(self.shuffle_mode.is_on())
+                 );
                  if shuffle_button.clicked() {
-                     self.is_shuffle = !self.is_shuffle;
-                     self.status = format!("Shuffle mode: {}", if self.is_shuffle { "ON" } else { "OFF" });
+                     self.set_shuffle_mode(self.shuffle_mode.next());
                  }
-                 shuffle_button.on_hover_text(format!("Turn Shuffle {}", if self.is_shuffle { "OFF" } else { "ON" }));
+                 let shuffle_button = shuffle_button.on_hover_text(format!("{} (right-click for all modes)", self.shuffle_mode.description()));
+                 shuffle_button.context_menu(|ui| {
+                     for mode in ShuffleMode::ALL {
+                         if ui.radio(self.shuffle_mode == mode, mode.description()).clicked() {
+                             self.set_shuffle_mode(mode);
+                             ui.close_menu();
+                         }
+                     }
+                 });
 
                  let repeat_icon = self.repeat_mode.icon();
                  let repeat_button = ui.add_enabled(
@@ -586,100 +2332,134 @@ impl eframe::App for MusicPlayerApp {
                 if volume_slider.changed() {
                     self.send_audio_command(AudioCommand::SetVolume(self.volume));
                 }
+                ui.add_space(8.0);
+                // Parado ou pausado não sai áudio: as barras caem até zerar
+                let levels = if self.is_playing { self.sample_tap.take_levels() } else { self.sample_tap.clear_levels(); Default::default() };
+                self.level_meters.update(levels, Duration::from_secs_f32(ctx.input(|i| i.unstable_dt).min(0.1)));
+                self.level_meters.show(ui);
+                if self.level_meters.is_active() { ctx.request_repaint_after(Duration::from_millis(33)); }
             });
+            let can_loop = (self.is_playing || self.is_paused) && self.current_track_duration.is_some_and(|total| total > Duration::ZERO);
+            let position = self.calculate_elapsed();
+            let playing_path = self.now_playing_path();
+            let (mut practice_action, mut bookmark_action) = (None, None);
+            ui.horizontal(|ui| {
+                practice_action = practice::controls(ui, &self.ab_loop, position, can_loop);
+                ui.add_space(12.0);
+                let bookmarks = playing_path.as_deref().map_or(&[][..], |path| self.library.bookmarks(path));
+                bookmark_action = bookmarks::menu(ui, bookmarks, position, &mut self.bookmark_name, can_loop);
+            });
+            if let Some(action) = practice_action { self.apply_practice_action(action); }
+            if let (Some(action), Some(path)) = (bookmark_action, &playing_path) { self.apply_bookmark_action(path, action); }
+            if let Some(speed) = playback_speed::controls(ui, self.playback_speed) {
+                self.set_playback_speed(speed);
+            }
             ui.separator();
 
              // --- Controles da Playlist (Layout Original) ---
              ui.horizontal(|ui| {
-                 let can_manage_playlist = !self.is_loading && self.audio_command_sender.is_some();
+                 let can_manage_playlist = !self.is_loading && self.audio_command_sender.is_some() && !self.active_tab().is_smart();
                  let add_button = ui.add_enabled(can_manage_playlist, egui::Button::new("➕ Add"));
                  if add_button.clicked() {
-                     if let Some(paths) = FileDialog::new().add_filter("Audio Files", &["mp3", "wav", "ogg", "flac"]).pick_files() {
+                     if let Some(picked) = FileDialog::new().add_filter("Audio Files", &files::ADD_DIALOG_EXTENSIONS).pick_files() {
+                         let paths = files::collect_audio_files(&picked);
                          if !paths.is_empty() {
                              let num_added = paths.len();
-                             let was_empty = self.playlist.is_empty();
-                             self.playlist.extend(paths);
+                             self.active_tab_mut().append(paths);
                              self.status = format!("Added {} file(s) to playlist.", num_added);
                              self.error_message = None;
-                             if was_empty { self.selected_track_index = Some(0); }
                          } else {
                              if self.error_message.is_none() { self.status = "File selection cancelled.".to_string(); }
                          }
                      }
                  }
-                 let can_clear = !self.playlist.is_empty() && can_manage_playlist;
+                 let can_clear = !self.active_tab().tracks.is_empty() && can_manage_playlist;
                  let clear_button = ui.add_enabled(can_clear, egui::Button::new("🗑️ Clear"));
                  if clear_button.clicked() {
-                     if self.is_playing || self.is_paused { self.send_audio_command(AudioCommand::Stop); }
-                     self.playlist.clear();
-                     self.selected_track_index = None;
+                     let clearing_playing = self.active_is_playing();
+                     if clearing_playing && (self.is_playing || self.is_paused) { self.send_audio_command(AudioCommand::Stop); }
+                     self.active_tab_mut().clear();
                      self.status = "Playlist cleared.".to_string();
-                     self.reset_playback_state();
+                     if clearing_playing { self.reset_playback_state(); }
                  }
-                 let can_remove = self.selected_track_index.is_some() && can_manage_playlist;
+                 let can_remove = self.active_tab().has_selection() && can_manage_playlist;
                  let remove_button = ui.add_enabled(can_remove, egui::Button::new("➖ Remove"));
                  if remove_button.clicked() {
-                     if let Some(index_to_remove) = self.selected_track_index {
-                         if index_to_remove < self.playlist.len() {
-                             let was_playing_removed = self.current_track_index == Some(index_to_remove);
-                             if was_playing_removed { self.send_audio_command(AudioCommand::Stop); }
-                             let removed_path = self.playlist.remove(index_to_remove);
-                             self.status = format!("Removed: {}", self.get_filename(&removed_path));
-                             self.current_track_index = self.current_track_index.and_then(|current_idx| {
-                                 if current_idx == index_to_remove { None }
-                                 else if current_idx > index_to_remove { Some(current_idx - 1) }
-                                 else { Some(current_idx) }
-                             });
-                             self.selected_track_index = if self.playlist.is_empty() { None }
-                             else if index_to_remove >= self.playlist.len() { Some(self.playlist.len() - 1) }
-                             else { Some(index_to_remove) };
-                             if was_playing_removed { self.reset_playback_state(); }
-                         }
-                     }
+                     let selection = self.active_tab().selected_indices_sorted();
+                     self.remove_tracks(&selection);
+                 }
+                 let can_move = self.active_tab().has_selection() && can_manage_playlist;
+                 if ui.add_enabled(can_move, egui::Button::new("⏫")).on_hover_text("Move selection to top").clicked() {
+                     self.apply_playlist_action(ctx, PlaylistAction::MoveToTop);
                  }
+                 if ui.add_enabled(can_move, egui::Button::new("⏬")).on_hover_text("Move selection to bottom").clicked() {
+                     self.apply_playlist_action(ctx, PlaylistAction::MoveToBottom);
+                 }
+                 let queue_label = format!("📃 Queue ({})", self.play_queue.len());
+                 ui.toggle_value(&mut self.show_queue_panel, queue_label).on_hover_text("Show the play queue");
+                 ui.toggle_value(&mut self.show_visualizer, "📊 Visualizer").on_hover_text("Show the spectrum analyzer and oscilloscope");
+                 ui.toggle_value(&mut self.show_lyrics_panel, "🎤 Lyrics").on_hover_text("Show synchronized lyrics (.lrc or embedded)");
              });
             ui.separator();
 
-            // --- Exibição da Playlist (Layout Original) ---
-            ui.label("Playlist:");
-            let mut play_clicked_index: Option<usize> = None;
-            egui::ScrollArea::vertical()
-                .auto_shrink([false, true])
-                .max_height(ui.available_height() * 0.5)
-                .show(ui, |ui| {
-                    if self.playlist.is_empty() {
-                        ui.weak("(Empty)");
-                    } else {
-                        let current_track_idx_display = self.current_track_index;
-                        let is_playing_display = self.is_playing;
-                        let is_paused_display = self.is_paused;
-                        for (index, path) in self.playlist.iter().enumerate() {
-                            let filename = self.get_filename(path);
-                            let is_current_track = current_track_idx_display == Some(index);
-                            let item_text = if is_current_track && is_playing_display {
-                                format!("▶ {}", filename)
-                            } else if is_current_track && is_paused_display {
-                                format!("⏸ {}", filename)
-                            } else {
-                                format!("  {}", filename)
-                            };
-                            let response = ui.selectable_label(self.selected_track_index == Some(index), item_text);
-                            if response.clicked() {
-                                self.selected_track_index = Some(index);
-                                let should_play = match (current_track_idx_display, is_playing_display, is_paused_display) {
-                                    (Some(current_idx), true, false) if current_idx == index => false,
-                                    (Some(current_idx), false, true) if current_idx == index => true,
-                                    _ => true,
-                                };
-                                if should_play { play_clicked_index = Some(index); }
-                            }
-                            response.on_hover_text(path.display().to_string());
-                        }
+            // --- Abas de Playlist ---
+            self.show_playlist_tabs(ui);
+
+            // --- Busca na Playlist ---
+            let search_id = egui::Id::new("playlist_search");
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::F)) {
+                ctx.memory_mut(|m| m.request_focus(search_id));
+            }
+            ui.horizontal(|ui| {
+                ui.label("🔍");
+                let search_edit = ui.add(
+                    egui::TextEdit::singleline(&mut self.search_query)
+                        .id(search_id)
+                        .hint_text("Search title, artist, album or file (Ctrl+F)")
+                        .desired_width(ui.available_width() - 130.0)
+                );
+                if search_edit.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    self.search_query.clear();
+                }
+                if ui.add_enabled(!self.search_query.is_empty(), egui::Button::new("✖").small()).on_hover_text("Clear search").clicked() {
+                    self.search_query.clear();
+                }
+                let rating_label = if self.rating_filter == 0 { "★ Any".to_string() } else { format!("★ ≥ {}", self.rating_filter) };
+                egui::ComboBox::from_id_source("rating_filter").selected_text(rating_label).width(60.0).show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.rating_filter, 0, "Any rating");
+                    for value in 1..=rating::MAX_RATING {
+                        ui.selectable_value(&mut self.rating_filter, value, format!("{} or more", rating::stars_text(value)));
                     }
                 });
+                ui.toggle_value(&mut self.loved_filter, "♥").on_hover_text("Only loved tracks");
+            });
+
+            // --- Exibição da Playlist (Layout Original) ---
+            let visible_indices = self.filtered_playlist_indices();
+            let text_has_focus = ctx.memory(|m| m.focused().is_some());
+            if !text_has_focus && !self.is_loading {
+                if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::A)) {
+                    self.active_tab_mut().selected_indices = visible_indices.iter().copied().collect();
+                }
+                if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Delete)) {
+                    self.apply_playlist_action(ctx, PlaylistAction::Remove);
+                }
+            }
+            if !self.search_query.trim().is_empty() || self.rating_filter > 0 || self.loved_filter {
+                ui.weak(format!("{} of {} tracks match", visible_indices.len(), self.active_tab().tracks.len()));
+            }
+            let play_clicked_index = if self.active_tab().tracks.is_empty() {
+                ui.weak("(Empty)");
+                None
+            } else if visible_indices.is_empty() {
+                ui.weak(if self.metadata.is_loading() { "(Searching...)" } else { "(No matches)" });
+                None
+            } else {
+                self.show_playlist_table(ui, &visible_indices)
+            };
 
             if let Some(index_to_play) = play_clicked_index {
-                self.play_track_at_index(index_to_play);
+                self.play_track_in_active(index_to_play);
             }
             ui.separator();
 
@@ -700,6 +2480,21 @@ impl eframe::App for MusicPlayerApp {
                 ui.weak(&self.status); // Status normal em cinza
             }
 
+            // --- Aviso enquanto arquivos externos estão sobre a janela ---
+            if hovering_files {
+                let screen_rect = ctx.screen_rect();
+                let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("file_drop_overlay")));
+                painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(120));
+                painter.text(screen_rect.center(), egui::Align2::CENTER_CENTER, "Drop files or folders to add them to the playlist",
+                    egui::TextStyle::Heading.resolve(&ctx.style()), egui::Color32::WHITE);
+                if let Some(pos) = ctx.input(|i| i.pointer.latest_pos()) {
+                    if let Some((_, rect)) = self.playlist_row_rects.iter().find(|(_, rect)| rect.contains(pos)) {
+                        let y = if pos.y > rect.center().y { rect.bottom() } else { rect.top() };
+                        painter.hline(rect.x_range(), y, egui::Stroke::new(2.0, ctx.style().visuals.selection.stroke.color));
+                    }
+                }
+            }
+
             // --- Solicitar Repaint ---
             if self.is_loading || self.is_playing {
                 ctx.request_repaint_after(Duration::from_millis(100));
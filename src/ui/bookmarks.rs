@@ -0,0 +1,48 @@
+// src/ui/bookmarks.rs
+// Menu dos marcadores da faixa atual: adicionar um com nome na posição atual, pular para
+// um marcador ou apagá-lo. Os marcadores ficam no banco da biblioteca, por arquivo.
+
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::library::Bookmark;
+use super::practice::format_position;
+
+pub enum BookmarkAction {
+    Add(Bookmark),
+    Jump(Duration),
+    Remove(usize),
+}
+
+// `name` é o texto em edição (mantido por quem chama entre os frames)
+pub fn menu(ui: &mut egui::Ui, bookmarks: &[Bookmark], position: Duration, name: &mut String, enabled: bool) -> Option<BookmarkAction> {
+    let mut action = None;
+    let label = if bookmarks.is_empty() { "🔖 Bookmarks".to_string() } else { format!("🔖 Bookmarks ({})", bookmarks.len()) };
+    ui.add_enabled_ui(enabled, |ui| {
+        ui.menu_button(label, |ui| {
+            ui.horizontal(|ui| {
+                let edit = ui.add(egui::TextEdit::singleline(name).hint_text("Bookmark name").desired_width(140.0));
+                let submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button(format!("Add at {}", format_position(position))).clicked() || submitted {
+                    let trimmed = name.trim();
+                    let name = if trimmed.is_empty() { format!("Bookmark {}", bookmarks.len() + 1) } else { trimmed.to_string() };
+                    action = Some(BookmarkAction::Add(Bookmark { name, position }));
+                }
+            });
+            ui.separator();
+            if bookmarks.is_empty() { ui.weak("No bookmarks for this file"); }
+            for (index, bookmark) in bookmarks.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("🗑").on_hover_text("Delete bookmark").clicked() { action = Some(BookmarkAction::Remove(index)); }
+                    if ui.button(format!("{}  {}", format_position(bookmark.position), bookmark.name)).on_hover_text("Jump to this bookmark").clicked() {
+                        action = Some(BookmarkAction::Jump(bookmark.position));
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+    });
+    if matches!(action, Some(BookmarkAction::Add(_))) { name.clear(); }
+    action
+}
@@ -0,0 +1,107 @@
+// src/ui/level_meter.rs
+// Medidores de nível estéreo (L/R) ao lado do volume: barra de RMS, barra de pico, marca de
+// pico retido e indicador de clip, que fica aceso até ser clicado.
+
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::sample_tap::ChannelLevel;
+
+const MIN_DB: f32 = -60.0;
+const PEAK_HOLD: Duration = Duration::from_millis(1500);
+// Queda das barras por segundo (dB), como nos medidores analógicos
+const FALL_DB_PER_SEC: f32 = 24.0;
+const METER_SIZE: egui::Vec2 = egui::vec2(120.0, 5.0);
+const CLIP_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 40, 40);
+
+#[derive(Clone, Copy)]
+struct ChannelMeter {
+    peak_db: f32,
+    rms_db: f32,
+    hold_db: f32,
+    hold_age: Duration,
+    clipped: bool,
+}
+
+impl Default for ChannelMeter {
+    fn default() -> Self {
+        ChannelMeter { peak_db: MIN_DB, rms_db: MIN_DB, hold_db: MIN_DB, hold_age: Duration::ZERO, clipped: false }
+    }
+}
+
+#[derive(Default)]
+pub struct LevelMeters {
+    channels: [ChannelMeter; 2],
+}
+
+impl LevelMeters {
+    // Junta as leituras do frame; `dt` é o tempo desde o frame anterior
+    pub fn update(&mut self, levels: [ChannelLevel; 2], dt: Duration) {
+        let fall = FALL_DB_PER_SEC * dt.as_secs_f32();
+        for (meter, level) in self.channels.iter_mut().zip(levels) {
+            let peak_db = to_db(level.peak);
+            meter.peak_db = peak_db.max(meter.peak_db - fall).max(MIN_DB);
+            meter.rms_db = to_db(level.rms).max(meter.rms_db - fall).max(MIN_DB);
+            meter.hold_age += dt;
+            if peak_db >= meter.hold_db || meter.hold_age > PEAK_HOLD {
+                meter.hold_db = peak_db.max(MIN_DB);
+                meter.hold_age = Duration::ZERO;
+            }
+            meter.clipped |= level.clipped;
+        }
+    }
+
+    // Ainda há algo a desenhar (as barras caem depois da pausa)
+    pub fn is_active(&self) -> bool {
+        self.channels.iter().any(|meter| meter.peak_db > MIN_DB || meter.rms_db > MIN_DB || meter.hold_db > MIN_DB)
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.vertical(|ui| {
+            ui.spacing_mut().item_spacing.y = 2.0;
+            for (meter, label) in self.channels.iter_mut().zip(["L", "R"]) {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 3.0;
+                    ui.label(egui::RichText::new(label).small().weak());
+                    paint_meter(ui, meter);
+                    let clip_color = if meter.clipped { CLIP_COLOR } else { ui.visuals().widgets.inactive.bg_fill };
+                    let (rect, response) = ui.allocate_exact_size(egui::vec2(METER_SIZE.y + 2.0, METER_SIZE.y + 2.0), egui::Sense::click());
+                    ui.painter().circle_filled(rect.center(), rect.width() / 2.0, clip_color);
+                    if response.clicked() { meter.clipped = false; }
+                    response.on_hover_text(if meter.clipped { "Clipped! Click to reset" } else { "Clip indicator" });
+                });
+            }
+        }).response.on_hover_text(self.describe());
+    }
+
+    fn describe(&self) -> String {
+        let [left, right] = self.channels;
+        let db = |value: f32| if value <= MIN_DB { "-∞".to_string() } else { format!("{:.1}", value) };
+        format!("Peak  L {} / R {} dBFS\nRMS   L {} / R {} dBFS", db(left.hold_db), db(right.hold_db), db(left.rms_db), db(right.rms_db))
+    }
+}
+
+fn to_db(value: f32) -> f32 {
+    if value <= 0.0 { MIN_DB } else { 20.0 * value.log10() }
+}
+
+fn paint_meter(ui: &mut egui::Ui, meter: &ChannelMeter) {
+    let (rect, _) = ui.allocate_exact_size(METER_SIZE, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 1.0, visuals.extreme_bg_color);
+    let x = |db: f32| rect.left() + ((db - MIN_DB) / -MIN_DB).clamp(0.0, 1.0) * rect.width();
+    // Pico (mais claro) por trás do RMS
+    let base = visuals.selection.bg_fill;
+    let peak_color = base.gamma_multiply(0.45);
+    let bar = |db: f32| egui::Rect::from_min_max(rect.min, egui::pos2(x(db), rect.bottom()));
+    painter.rect_filled(bar(meter.peak_db), 1.0, peak_color);
+    painter.rect_filled(bar(meter.rms_db), 1.0, base);
+    // Zona acima de -6 dBFS e marca do pico retido
+    painter.vline(x(-6.0), rect.y_range(), egui::Stroke::new(1.0, visuals.faint_bg_color));
+    if meter.hold_db > MIN_DB {
+        let hold_color = if meter.hold_db >= 0.0 { CLIP_COLOR } else { visuals.strong_text_color() };
+        painter.vline(x(meter.hold_db), rect.y_range(), egui::Stroke::new(1.5, hold_color));
+    }
+}
@@ -0,0 +1,64 @@
+// src/ui/lyrics_view.rs
+// Painel de letras: carrega a letra da faixa que está tocando, destaca a linha atual pela
+// posição real de reprodução e rola até ela. Clicar em uma linha sincronizada pede um seek.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::lyrics::{self, Lyrics};
+
+#[derive(Default)]
+pub struct LyricsView {
+    path: Option<PathBuf>,
+    lyrics: Option<Lyrics>,
+    // Linha destacada no último frame; a rolagem só acompanha quando ela muda,
+    // para o usuário poder rolar à vontade entre uma linha e outra
+    last_line: Option<usize>,
+}
+
+impl LyricsView {
+    // Lê a letra quando a faixa muda (None = nada tocando)
+    pub fn set_track(&mut self, path: Option<&Path>) {
+        if self.path.as_deref() == path { return; }
+        self.path = path.map(Path::to_path_buf);
+        self.lyrics = path.and_then(lyrics::load);
+        self.last_line = None;
+    }
+
+    // Devolve o tempo da linha clicada
+    pub fn show(&mut self, ui: &mut egui::Ui, position: Duration) -> Option<Duration> {
+        let Some(lyrics) = &self.lyrics else {
+            let text = if self.path.is_some() { "No lyrics found. Put a .lrc file with the same name next to the track, or embed lyrics in its tags." } else { "Nothing playing." };
+            ui.weak(text);
+            return None;
+        };
+        ui.weak(if lyrics.is_synced() { format!("{} · click a line to jump to it", lyrics.source) } else { lyrics.source.clone() });
+        ui.separator();
+
+        let current = lyrics.current_line(position);
+        let follow = current != self.last_line;
+        self.last_line = current;
+        let mut seek_to = None;
+        egui::ScrollArea::vertical().id_source("lyrics_scroll").auto_shrink([false, false]).show(ui, |ui| {
+            for (index, line) in lyrics.lines.iter().enumerate() {
+                let text = if line.text.is_empty() { "♪" } else { line.text.as_str() };
+                let is_current = Some(index) == current;
+                let rich = if is_current { egui::RichText::new(text).strong().size(ui.text_style_height(&egui::TextStyle::Body) * 1.15) }
+                    else if current.is_some_and(|c| index < c) { egui::RichText::new(text).weak() }
+                    else { egui::RichText::new(text) };
+                let response = match line.time {
+                    Some(time) => {
+                        let response = ui.add(egui::Label::new(rich).wrap(true).sense(egui::Sense::click()).selectable(false));
+                        if response.clicked() { seek_to = Some(time); }
+                        response.on_hover_cursor(egui::CursorIcon::PointingHand)
+                    }
+                    None => ui.add(egui::Label::new(rich).wrap(true)),
+                };
+                if is_current && follow { response.scroll_to_me(Some(egui::Align::Center)); }
+            }
+        });
+        seek_to
+    }
+}
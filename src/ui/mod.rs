@@ -1,2 +1,18 @@
 // src/ui/mod.rs
 pub mod app;
+pub mod bookmarks;
+pub mod level_meter;
+pub mod lyrics_view;
+pub mod organize;
+pub mod playback_speed;
+pub mod play_queue;
+pub mod playlist_table;
+pub mod playlist_tabs;
+pub mod practice;
+pub mod rating;
+pub mod search;
+pub mod shuffle;
+pub mod smart_playlist;
+pub mod tag_editor;
+pub mod visualizer;
+pub mod waveform_bar;
@@ -0,0 +1,138 @@
+// src/ui/organize.rs
+// Janela do organizador: modelo, pasta de destino e a prévia do que vai ser movido.
+// As tags são lidas uma vez ao abrir; a prévia é refeita quando o modelo, a pasta ou a faixa tocando mudam.
+
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use rfd::FileDialog;
+
+use crate::organizer::{self, MoveStatus, PlannedMove, TEMPLATE_FIELDS};
+use crate::tag_writer::TagFields;
+
+pub enum OrganizeAction {
+    Run { planned: Vec<PlannedMove>, root: PathBuf },
+    Close,
+}
+
+pub struct OrganizeWindow {
+    sources: Vec<(PathBuf, Result<TagFields, String>)>,
+    in_use: Option<PathBuf>,
+    pub template: String,
+    pub root: PathBuf,
+    preview: Result<Vec<PlannedMove>, String>,
+    preview_key: Option<(String, PathBuf, Option<PathBuf>)>,
+}
+
+impl OrganizeWindow {
+    pub fn open(paths: Vec<PathBuf>, template: String, root: PathBuf, in_use: Option<PathBuf>) -> Self {
+        let sources = paths.into_iter().map(|path| {
+            let tags = TagFields::read_without_cover(&path);
+            (path, tags)
+        }).collect();
+        OrganizeWindow { sources, in_use, template, root, preview: Ok(Vec::new()), preview_key: None }
+    }
+
+    // Arquivo aberto na thread de áudio; muda quando a faixa troca com a janela aberta
+    pub fn set_in_use(&mut self, in_use: Option<PathBuf>) {
+        self.in_use = in_use;
+    }
+
+    fn refresh_preview(&mut self) {
+        let key = (self.template.clone(), self.root.clone(), self.in_use.clone());
+        if self.preview_key.as_ref() == Some(&key) { return; }
+        self.preview = organizer::plan(&self.sources, &self.template, &self.root, self.in_use.as_deref());
+        self.preview_key = Some(key);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<OrganizeAction> {
+        self.refresh_preview();
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new(format!("Organize files ({} tracks)", self.sources.len()))
+            .open(&mut open)
+            .default_size([720.0, 420.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::Grid::new("organize_grid").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+                    ui.label("Template:");
+                    ui.add(egui::TextEdit::singleline(&mut self.template).desired_width(f32::INFINITY).font(egui::TextStyle::Monospace));
+                    ui.end_row();
+                    ui.label("");
+                    let fields: Vec<String> = TEMPLATE_FIELDS.iter().map(|field| format!("{{{}}}", field)).collect();
+                    ui.weak(format!("Fields: {}. Numbers can be padded: {{track:02}}.", fields.join(" ")));
+                    ui.end_row();
+                    ui.label("Destination:");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Label::new(self.root.display().to_string()).truncate(true));
+                        if ui.button("Choose...").clicked() {
+                            if let Some(dir) = FileDialog::new().set_directory(&self.root).pick_folder() { self.root = dir; }
+                        }
+                    });
+                    ui.end_row();
+                });
+                ui.separator();
+
+                let planned = match &self.preview {
+                    Ok(planned) => planned,
+                    Err(e) => {
+                        ui.colored_label(egui::Color32::RED, e);
+                        return;
+                    }
+                };
+                let count = |wanted: fn(&MoveStatus) -> bool| planned.iter().filter(|m| wanted(&m.status)).count();
+                let moving = count(|s| *s == MoveStatus::Move);
+                ui.label(format!(
+                    "{} to move, {} already in place, {} collision(s), {} skipped. Collisions and skipped files are left where they are.",
+                    moving, count(|s| *s == MoveStatus::Unchanged), count(|s| matches!(s, MoveStatus::Collision(_))), count(|s| matches!(s, MoveStatus::Skipped(_))),
+                ));
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(moving > 0, egui::Button::new(format!("📁 Move {} file(s)", moving))).clicked() {
+                        action = Some(OrganizeAction::Run { planned: planned.clone(), root: self.root.clone() });
+                    }
+                    if ui.button("Cancel").clicked() { action = Some(OrganizeAction::Close); }
+                });
+                ui.separator();
+                preview_table(ui, planned, &self.root);
+            });
+        if !open { action = Some(OrganizeAction::Close); }
+        action
+    }
+}
+
+fn preview_table(ui: &mut egui::Ui, planned: &[PlannedMove], root: &Path) {
+    let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+    egui_extras::TableBuilder::new(ui)
+        .striped(true)
+        .column(egui_extras::Column::initial(24.0))
+        .column(egui_extras::Column::initial(240.0).resizable(true).clip(true))
+        .column(egui_extras::Column::remainder().at_least(160.0).clip(true))
+        .header(row_height, |mut header| {
+            for title in ["", "Current file", "New path"] {
+                header.col(|ui| { ui.strong(title); });
+            }
+        })
+        .body(|body| {
+            body.rows(row_height, planned.len(), |mut row| {
+                let entry = &planned[row.index()];
+                let (icon, color, note) = match &entry.status {
+                    MoveStatus::Move => ("➡", egui::Color32::PLACEHOLDER, None), // Cor normal do tema
+                    MoveStatus::Unchanged => ("✔", egui::Color32::GRAY, Some("Already in place")),
+                    MoveStatus::Collision(reason) => ("⚠", egui::Color32::RED, Some(reason.as_str())),
+                    MoveStatus::Skipped(reason) => ("⏸", egui::Color32::GRAY, Some(reason.as_str())),
+                };
+                row.col(|ui| {
+                    let response = ui.label(egui::RichText::new(icon).color(color));
+                    if let Some(note) = note { response.on_hover_text(note); }
+                });
+                let from = entry.from.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                row.col(|ui| { ui.add(egui::Label::new(from).truncate(true)).on_hover_text(entry.from.display().to_string()); });
+                let to = match &entry.status {
+                    MoveStatus::Skipped(reason) => reason.clone(),
+                    _ => entry.to.strip_prefix(root).unwrap_or(&entry.to).display().to_string(),
+                };
+                row.col(|ui| { ui.add(egui::Label::new(egui::RichText::new(to).color(color)).truncate(true)).on_hover_text(entry.to.display().to_string()); });
+            });
+        });
+}
+
@@ -0,0 +1,60 @@
+// src/ui/play_queue.rs
+// Fila de reprodução: faixas escolhidas com "Play next" / "Add to queue" tocam antes
+// da ordem normal da playlist. A fila guarda caminhos, então não depende de aba nem de
+// índices, e não é salva ao fechar o player.
+
+use std::path::{Path, PathBuf};
+
+#[derive(Default)]
+pub struct PlayQueue {
+    tracks: Vec<PathBuf>,
+}
+
+impl PlayQueue {
+    pub fn tracks(&self) -> &[PathBuf] {
+        &self.tracks
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    // "Play next": as faixas entram no começo da fila, na ordem dada
+    pub fn push_front(&mut self, paths: Vec<PathBuf>) {
+        self.tracks.splice(0..0, paths);
+    }
+
+    // "Add to queue": as faixas entram no fim da fila
+    pub fn push_back(&mut self, paths: Vec<PathBuf>) {
+        self.tracks.extend(paths);
+    }
+
+    pub fn pop_front(&mut self) -> Option<PathBuf> {
+        if self.tracks.is_empty() { None } else { Some(self.tracks.remove(0)) }
+    }
+
+    // Move a entrada `index` para a posição `to` (limitada ao tamanho da fila)
+    pub fn move_entry(&mut self, index: usize, to: usize) {
+        if index >= self.tracks.len() { return; }
+        let path = self.tracks.remove(index);
+        let to = to.min(self.tracks.len());
+        self.tracks.insert(to, path);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.tracks.len() { self.tracks.remove(index); }
+    }
+
+    // Arquivo movido pelo organizador
+    pub fn rename_path(&mut self, from: &Path, to: &Path) {
+        for path in self.tracks.iter_mut().filter(|path| path.as_path() == from) { *path = to.to_path_buf(); }
+    }
+
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+    }
+}
@@ -0,0 +1,54 @@
+// src/ui/playback_speed.rs
+// Velocidade (0.5×–3×), opção de manter a altura e mudança de tom em semitons, independente
+// da velocidade. Os valores ficam salvos com o estado do app; quem chama manda os comandos.
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::playback_control::{MAX_PITCH_SEMITONES, MAX_SPEED, MIN_SPEED};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackSpeed {
+    pub rate: f32,
+    pub preserve_pitch: bool,
+    pub semitones: f32,
+}
+
+impl Default for PlaybackSpeed {
+    fn default() -> Self {
+        PlaybackSpeed { rate: 1.0, preserve_pitch: true, semitones: 0.0 }
+    }
+}
+
+impl PlaybackSpeed {
+    pub fn is_default(&self) -> bool {
+        self.rate == 1.0 && self.semitones == 0.0
+    }
+
+    pub fn describe(&self) -> String {
+        let mut text = format!("Speed {:.2}×", self.rate);
+        if self.rate != 1.0 && !self.preserve_pitch { text.push_str(" (pitch follows speed)"); }
+        if self.semitones != 0.0 { text.push_str(&format!(", pitch {:+} st", self.semitones)); }
+        text
+    }
+}
+
+// Devolve os novos valores quando algo muda
+pub fn controls(ui: &mut egui::Ui, current: PlaybackSpeed) -> Option<PlaybackSpeed> {
+    let mut speed = current;
+    ui.horizontal(|ui| {
+        ui.label("Speed:");
+        ui.add(egui::Slider::new(&mut speed.rate, MIN_SPEED..=MAX_SPEED).step_by(0.05).fixed_decimals(2).suffix("×"))
+            .on_hover_text("Playback speed");
+        ui.checkbox(&mut speed.preserve_pitch, "Keep pitch").on_hover_text("Change the speed without changing the pitch (time-stretch)");
+        ui.add_space(8.0);
+        ui.label("Pitch:");
+        ui.add(egui::Slider::new(&mut speed.semitones, -MAX_PITCH_SEMITONES..=MAX_PITCH_SEMITONES).step_by(1.0).fixed_decimals(0).suffix(" st"))
+            .on_hover_text("Pitch shift in semitones (independent of the speed)");
+        if ui.add_enabled(!current.is_default(), egui::Button::new("↺")).on_hover_text("Reset speed and pitch").clicked() {
+            speed = PlaybackSpeed { preserve_pitch: current.preserve_pitch, ..PlaybackSpeed::default() };
+        }
+    });
+    if speed != current { Some(speed) } else { None }
+}
@@ -0,0 +1,239 @@
+// src/ui/playlist_table.rs
+// Colunas da tabela da playlist e a lógica de ordenação por coluna.
+
+use std::cmp::Ordering;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::library::TrackStats;
+use crate::metadata::TrackInfo;
+use super::rating;
+
+// --- Colunas disponíveis ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlaylistColumn {
+    Number,
+    Title,
+    Artist,
+    Album,
+    Duration,
+    Bitrate,
+    Format,
+    Path,
+    Plays,
+    Skips,
+    Rating,
+    Loved,
+}
+
+impl PlaylistColumn {
+    pub const ALL: [PlaylistColumn; 12] = [
+        PlaylistColumn::Number,
+        PlaylistColumn::Title,
+        PlaylistColumn::Artist,
+        PlaylistColumn::Album,
+        PlaylistColumn::Duration,
+        PlaylistColumn::Bitrate,
+        PlaylistColumn::Format,
+        PlaylistColumn::Path,
+        PlaylistColumn::Plays,
+        PlaylistColumn::Skips,
+        PlaylistColumn::Rating,
+        PlaylistColumn::Loved,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlaylistColumn::Number => "#",
+            PlaylistColumn::Title => "Title",
+            PlaylistColumn::Artist => "Artist",
+            PlaylistColumn::Album => "Album",
+            PlaylistColumn::Duration => "Duration",
+            PlaylistColumn::Bitrate => "Bitrate",
+            PlaylistColumn::Format => "Format",
+            PlaylistColumn::Path => "Path",
+            PlaylistColumn::Plays => "Plays",
+            PlaylistColumn::Skips => "Skips",
+            PlaylistColumn::Rating => "Rating",
+            PlaylistColumn::Loved => "♥",
+        }
+    }
+
+    // Largura inicial de cada coluna (pode ser redimensionada pelo usuário)
+    pub fn initial_width(&self) -> f32 {
+        match self {
+            PlaylistColumn::Number => 40.0,
+            PlaylistColumn::Title => 180.0,
+            PlaylistColumn::Artist | PlaylistColumn::Album => 130.0,
+            PlaylistColumn::Duration | PlaylistColumn::Bitrate | PlaylistColumn::Format => 70.0,
+            PlaylistColumn::Path => 260.0,
+            PlaylistColumn::Plays | PlaylistColumn::Skips => 50.0,
+            PlaylistColumn::Rating => 80.0,
+            PlaylistColumn::Loved => 30.0,
+        }
+    }
+
+    // Colunas que dependem das tags lidas em segundo plano
+    pub fn needs_tags(&self) -> bool {
+        matches!(self, PlaylistColumn::Title | PlaylistColumn::Artist | PlaylistColumn::Album
+            | PlaylistColumn::Duration | PlaylistColumn::Bitrate | PlaylistColumn::Format)
+    }
+
+    // Texto exibido na célula
+    pub fn cell_text(&self, index: usize, path: &Path, filename: &str, info: Option<&TrackInfo>, stats: Option<&TrackStats>) -> String {
+        match self {
+            PlaylistColumn::Number => format!("{}", index + 1),
+            PlaylistColumn::Title => info.and_then(|i| i.title.clone()).unwrap_or_else(|| filename.to_string()),
+            PlaylistColumn::Artist => info.and_then(|i| i.artist.clone()).unwrap_or_default(),
+            PlaylistColumn::Album => info.and_then(|i| i.album.clone()).unwrap_or_default(),
+            PlaylistColumn::Duration => info.and_then(|i| i.duration)
+                .map(|d| format!("{:02}:{:02}", d.as_secs() / 60, d.as_secs() % 60))
+                .unwrap_or_default(),
+            PlaylistColumn::Bitrate => info.and_then(|i| i.bitrate).map(|b| format!("{} kbps", b)).unwrap_or_default(),
+            PlaylistColumn::Format => info.and_then(|i| i.format.clone()).unwrap_or_default(),
+            PlaylistColumn::Path => path.display().to_string(),
+            PlaylistColumn::Plays => format!("{}", stats.map_or(0, |s| s.plays)),
+            PlaylistColumn::Skips => format!("{}", stats.map_or(0, |s| s.skips)),
+            PlaylistColumn::Rating => rating::stars_text(stats.map_or(0, |s| s.rating)),
+            PlaylistColumn::Loved => if stats.is_some_and(|s| s.loved) { "♥".to_string() } else { String::new() },
+        }
+    }
+
+    // Compara duas entradas para esta coluna. Valores ausentes ficam no fim.
+    pub fn compare(&self, a: SortEntry<'_>, b: SortEntry<'_>) -> Ordering {
+        fn text_cmp(a: Option<&str>, b: Option<&str>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+        fn option_cmp<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+        match self {
+            PlaylistColumn::Number => a.index.cmp(&b.index),
+            PlaylistColumn::Title => text_cmp(
+                Some(a.info.and_then(|i| i.title.as_deref()).unwrap_or(a.filename)),
+                Some(b.info.and_then(|i| i.title.as_deref()).unwrap_or(b.filename)),
+            ),
+            PlaylistColumn::Artist => text_cmp(a.info.and_then(|i| i.artist.as_deref()), b.info.and_then(|i| i.artist.as_deref())),
+            PlaylistColumn::Album => text_cmp(a.info.and_then(|i| i.album.as_deref()), b.info.and_then(|i| i.album.as_deref())),
+            PlaylistColumn::Duration => option_cmp(a.info.and_then(|i| i.duration), b.info.and_then(|i| i.duration)),
+            PlaylistColumn::Bitrate => option_cmp(a.info.and_then(|i| i.bitrate), b.info.and_then(|i| i.bitrate)),
+            PlaylistColumn::Format => text_cmp(a.info.and_then(|i| i.format.as_deref()), b.info.and_then(|i| i.format.as_deref())),
+            PlaylistColumn::Path => a.path.cmp(b.path),
+            PlaylistColumn::Plays => a.stats.map_or(0, |s| s.plays).cmp(&b.stats.map_or(0, |s| s.plays)),
+            PlaylistColumn::Skips => a.stats.map_or(0, |s| s.skips).cmp(&b.stats.map_or(0, |s| s.skips)),
+            PlaylistColumn::Rating => a.stats.map_or(0, |s| s.rating).cmp(&b.stats.map_or(0, |s| s.rating)),
+            PlaylistColumn::Loved => a.stats.is_some_and(|s| s.loved).cmp(&b.stats.is_some_and(|s| s.loved)),
+        }
+    }
+}
+
+// Dados de uma entrada usados na comparação
+#[derive(Clone, Copy)]
+pub struct SortEntry<'a> {
+    pub index: usize,
+    pub path: &'a Path,
+    pub filename: &'a str,
+    pub info: Option<&'a TrackInfo>,
+    pub stats: Option<&'a TrackStats>,
+}
+
+// --- Estado de ordenação exibido no cabeçalho ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortState {
+    pub column: PlaylistColumn,
+    pub ascending: bool,
+}
+
+// Remapeia um índice depois de aplicar a permutação `order`
+// (onde order[novo_indice] == indice_antigo).
+pub fn remap_index(order: &[usize], old_index: Option<usize>) -> Option<usize> {
+    old_index.and_then(|old| order.iter().position(|&i| i == old))
+}
+
+// Permutação que move as entradas `moving` (índices antigos) para antes da posição
+// `insert_at` (também um índice antigo; pode ser igual ao tamanho da playlist para o fim).
+// As entradas movidas mantêm sua ordem relativa.
+pub fn move_order(len: usize, moving: &[usize], insert_at: usize) -> Vec<usize> {
+    let mut moving: Vec<usize> = moving.iter().copied().filter(|&i| i < len).collect();
+    moving.sort_unstable();
+    moving.dedup();
+    let mut order: Vec<usize> = (0..len).filter(|i| moving.binary_search(i).is_err()).collect();
+    let insert_pos = insert_at.min(len) - moving.iter().filter(|&&i| i < insert_at).count();
+    order.splice(insert_pos..insert_pos, moving);
+    order
+}
+
+// Permutação que sobe (ou desce) cada entrada de `moving` uma posição, trocando com a
+// vizinha não selecionada. Os espaços entre as entradas são mantidos; uma entrada já
+// encostada no topo (ou no fim) ou em outra parada fica onde está.
+pub fn shift_order(len: usize, moving: &[usize], up: bool) -> Vec<usize> {
+    let mut selected = vec![false; len];
+    for &i in moving.iter().filter(|&&i| i < len) { selected[i] = true; }
+    let mut order: Vec<usize> = (0..len).collect();
+    if up {
+        for i in 1..len {
+            if selected[order[i]] && !selected[order[i - 1]] { order.swap(i, i - 1); }
+        }
+    } else {
+        for i in (0..len.saturating_sub(1)).rev() {
+            if selected[order[i]] && !selected[order[i + 1]] { order.swap(i, i + 1); }
+        }
+    }
+    order
+}
+
+// Conteúdo arrastado dentro da tabela da playlist (índices reais)
+pub struct PlaylistDragPayload(pub Vec<usize>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_a_block_past_the_playing_index() {
+        // Faixas 1 e 2 vão para antes da 5; a 3 (tocando) sobe duas posições
+        let order = move_order(6, &[1, 2], 5);
+        assert_eq!(order, [0, 3, 4, 1, 2, 5]);
+        assert_eq!(remap_index(&order, Some(3)), Some(1));
+        assert_eq!(remap_index(&order, Some(2)), Some(4));
+        assert_eq!(remap_index(&order, Some(5)), Some(5));
+        assert_eq!(remap_index(&order, None), None);
+    }
+
+    #[test]
+    fn moving_onto_itself_keeps_the_order() {
+        let identity: Vec<usize> = (0..5).collect();
+        assert_eq!(move_order(5, &[1, 2], 1), identity);
+        assert_eq!(move_order(5, &[1, 2], 2), identity);
+        assert_eq!(move_order(5, &[1, 2], 3), identity);
+    }
+
+    #[test]
+    fn moves_to_the_end_and_ignores_bad_indices() {
+        let order = move_order(4, &[2, 0, 0, 9], 4);
+        assert_eq!(order, [1, 3, 0, 2]);
+        assert_eq!(remap_index(&order, Some(0)), Some(2));
+        assert_eq!(remap_index(&order, Some(1)), Some(0));
+        assert_eq!(move_order(4, &[1], 99), [0, 2, 3, 1]);
+    }
+
+    #[test]
+    fn shifting_keeps_gaps_between_selected_tracks() {
+        assert_eq!(shift_order(6, &[2, 4], true), [0, 2, 1, 4, 3, 5]);
+        assert_eq!(shift_order(6, &[2, 4], false), [0, 1, 3, 2, 5, 4]);
+        // Encostadas no topo/fim (ou em outra parada) ficam onde estão
+        assert_eq!(shift_order(5, &[0, 1, 3], true), [0, 1, 3, 2, 4]);
+        assert_eq!(shift_order(5, &[1, 4], false), [0, 2, 1, 3, 4]);
+    }
+}
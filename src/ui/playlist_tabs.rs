@@ -0,0 +1,186 @@
+// src/ui/playlist_tabs.rs
+// Playlists nomeadas exibidas como abas. Cada aba guarda suas faixas, sua seleção
+// e sua ordenação; a posição de rolagem fica na memória do egui, indexada pelo `id` da aba.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::playlist_table::{self, SortState};
+use super::smart_playlist::SmartRules;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaylistTab {
+    pub id: u64,
+    pub name: String,
+    pub tracks: Vec<PathBuf>,
+    pub selected_track_index: Option<usize>,
+    pub sort: Option<SortState>,
+    // Playlist inteligente: as faixas são recalculadas a partir das regras
+    pub smart: Option<SmartRules>,
+    #[serde(skip)]
+    pub selected_indices: BTreeSet<usize>,
+    // Aumenta a cada mudança nas faixas; as playlists inteligentes se recalculam quando ele muda
+    #[serde(skip)]
+    revision: u64,
+}
+
+impl Default for PlaylistTab {
+    fn default() -> Self {
+        PlaylistTab::new("Playlist 1")
+    }
+}
+
+impl PlaylistTab {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: rand::random(),
+            name: name.to_string(),
+            tracks: Vec::new(),
+            selected_track_index: None,
+            sort: None,
+            smart: None,
+            selected_indices: BTreeSet::new(),
+            revision: 0,
+        }
+    }
+
+    pub fn new_smart(name: &str, rules: SmartRules) -> Self {
+        PlaylistTab { smart: Some(rules), ..PlaylistTab::new(name) }
+    }
+
+    pub fn is_smart(&self) -> bool {
+        self.smart.is_some()
+    }
+
+    // Descarta índices que não existem mais (ex.: estado salvo inconsistente)
+    pub fn validate_selection(&mut self) {
+        if self.selected_track_index.is_some_and(|idx| idx >= self.tracks.len()) {
+            self.selected_track_index = None;
+        }
+        self.select_single(self.selected_track_index);
+    }
+
+    // --- Seleção (selected_track_index é a faixa em foco) ---
+    pub fn select_single(&mut self, index: Option<usize>) {
+        self.selected_track_index = index;
+        self.selected_indices = index.into_iter().collect();
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.selected_track_index.is_some() || !self.selected_indices.is_empty()
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected_indices.contains(&index) || self.selected_track_index == Some(index)
+    }
+
+    // Seleção ordenada; se nada estiver marcado, usa a faixa em foco
+    pub fn selected_indices_sorted(&self) -> Vec<usize> {
+        if self.selected_indices.is_empty() {
+            self.selected_track_index.into_iter().collect()
+        } else {
+            self.selected_indices.iter().copied().collect()
+        }
+    }
+
+    // Clique com Ctrl alterna a faixa; com Shift seleciona o intervalo desde a faixa em foco
+    pub fn handle_selection_click(&mut self, index: usize, shift: bool, command: bool, visible_indices: &[usize]) {
+        if shift {
+            let anchor = self.selected_track_index.unwrap_or(index);
+            let anchor_pos = visible_indices.iter().position(|&i| i == anchor).unwrap_or(0);
+            let clicked_pos = visible_indices.iter().position(|&i| i == index).unwrap_or(0);
+            let (start, end) = if anchor_pos <= clicked_pos { (anchor_pos, clicked_pos) } else { (clicked_pos, anchor_pos) };
+            if !command { self.selected_indices.clear(); }
+            self.selected_indices.extend(visible_indices[start..=end].iter().copied());
+        } else if command {
+            if self.selected_indices.remove(&index) {
+                // A faixa desmarcada não pode continuar em foco (ela contaria como selecionada);
+                // o foco passa para a marcada mais próxima, ou some junto com a seleção
+                let before = self.selected_indices.range(..index).next_back();
+                self.selected_track_index = before.or_else(|| self.selected_indices.range(index..).next()).copied();
+            } else {
+                self.selected_indices.insert(index);
+                self.selected_track_index = Some(index);
+            }
+        }
+    }
+
+    // --- Edição ---
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    // Reordena as faixas segundo `order` (order[novo] == antigo), levando a seleção junto
+    pub fn apply_order(&mut self, order: &[usize]) {
+        self.tracks = order.iter().map(|&i| self.tracks[i].clone()).collect();
+        self.revision += 1;
+        self.selected_track_index = playlist_table::remap_index(order, self.selected_track_index);
+        self.selected_indices = self.selected_indices.iter()
+            .filter_map(|&old| playlist_table::remap_index(order, Some(old)))
+            .collect();
+    }
+
+    // Insere faixas em `at` e seleciona as novas entradas
+    pub fn insert(&mut self, at: usize, paths: Vec<PathBuf>) {
+        let count = paths.len();
+        let was_empty = self.tracks.is_empty();
+        self.tracks.splice(at..at, paths);
+        self.revision += 1;
+        if was_empty {
+            self.select_single(Some(0));
+        } else {
+            self.selected_track_index = self.selected_track_index.map(|i| if i >= at { i + count } else { i });
+            self.selected_indices = (at..at + count).collect();
+        }
+        self.sort = None;
+    }
+
+    // Acrescenta faixas no fim sem mexer na seleção (só seleciona a primeira se a lista estava vazia)
+    pub fn append(&mut self, paths: Vec<PathBuf>) {
+        let was_empty = self.tracks.is_empty();
+        self.tracks.extend(paths);
+        self.revision += 1;
+        if was_empty { self.select_single(Some(0)); }
+    }
+
+    // Remove os índices (ordenados, sem repetição) e coloca o foco na posição do primeiro removido
+    pub fn remove_sorted(&mut self, to_remove: &[usize]) {
+        let Some(&first_removed) = to_remove.first() else { return; };
+        for &index in to_remove.iter().rev() {
+            self.tracks.remove(index);
+        }
+        self.revision += 1;
+        let new_selection = if self.tracks.is_empty() { None }
+        else if first_removed >= self.tracks.len() { Some(self.tracks.len() - 1) }
+        else { Some(first_removed) };
+        self.select_single(new_selection);
+    }
+
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.revision += 1;
+        self.select_single(None);
+        self.sort = None;
+    }
+
+    // Arquivo movido pelo organizador
+    pub fn rename_path(&mut self, from: &Path, to: &Path) {
+        let mut renamed = false;
+        for path in self.tracks.iter_mut().filter(|path| path.as_path() == from) {
+            *path = to.to_path_buf();
+            renamed = true;
+        }
+        if renamed { self.revision += 1; }
+    }
+}
+
+// Nome livre para uma nova aba ("Playlist N")
+pub fn next_playlist_name(tabs: &[PlaylistTab]) -> String {
+    (1..)
+        .map(|n| format!("Playlist {}", n))
+        .find(|name| !tabs.iter().any(|tab| &tab.name == name))
+        .unwrap_or_default()
+}
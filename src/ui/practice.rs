@@ -0,0 +1,125 @@
+// src/ui/practice.rs
+// Modo de prática: pontos A e B do laço (botões na posição atual ou menu da barra de
+// progresso). O andamento fica nos controles de velocidade (playback_speed). Quem chama
+// manda os comandos.
+
+use std::time::Duration;
+
+use eframe::egui;
+
+// Pontos do laço relativos ao início da faixa; só vira laço com os dois marcados
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AbLoop {
+    pub start: Option<Duration>,
+    pub end: Option<Duration>,
+}
+
+impl AbLoop {
+    pub fn region(&self) -> Option<(Duration, Duration)> {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if start < end => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    // Um A depois do B descarta o B
+    pub fn set_start(&mut self, position: Duration) {
+        self.start = Some(position);
+        if self.end.is_some_and(|end| end <= position) { self.end = None; }
+    }
+
+    // Sem A, o laço começa no início da faixa; um B antes do A troca os dois
+    pub fn set_end(&mut self, position: Duration) {
+        let start = self.start.unwrap_or(Duration::ZERO);
+        if position > start { (self.start, self.end) = (Some(start), Some(position)); }
+        else if position < start { (self.start, self.end) = (Some(position), Some(start)); }
+    }
+
+    pub fn describe(&self) -> String {
+        match (self.start, self.end) {
+            (None, _) => "Loop off".to_string(),
+            (Some(start), None) => format!("A {} – B ?", format_position(start)),
+            (Some(start), Some(end)) => format!("A {} – B {}", format_position(start), format_position(end)),
+        }
+    }
+}
+
+pub enum PracticeAction {
+    SetStart(Duration),
+    SetEnd(Duration),
+    ClearLoop,
+}
+
+// `position` é a posição atual (para os botões A e B); desenha na linha de quem chama
+pub fn controls(ui: &mut egui::Ui, ab_loop: &AbLoop, position: Duration, can_loop: bool) -> Option<PracticeAction> {
+    let mut action = None;
+    ui.label("Loop:");
+    if ui.add_enabled(can_loop, egui::Button::new("A").selected(ab_loop.start.is_some())).on_hover_text("Set loop start at the current position").clicked() {
+        action = Some(PracticeAction::SetStart(position));
+    }
+    if ui.add_enabled(can_loop, egui::Button::new("B").selected(ab_loop.end.is_some())).on_hover_text("Set loop end at the current position").clicked() {
+        action = Some(PracticeAction::SetEnd(position));
+    }
+    if ui.add_enabled(ab_loop.start.is_some(), egui::Button::new("✖")).on_hover_text("Clear the A-B loop").clicked() {
+        action = Some(PracticeAction::ClearLoop);
+    }
+    let label = egui::RichText::new(ab_loop.describe());
+    ui.label(if ab_loop.region().is_some() { label.strong() } else { label.weak() })
+        .on_hover_text("Right-click the progress bar to set A or B at any point");
+    action
+}
+
+// mm:ss.d — os pontos do laço precisam de mais precisão que o relógio da barra
+pub fn format_position(position: Duration) -> String {
+    let tenths = position.as_millis() / 100;
+    format!("{:02}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn loops_only_with_both_points() {
+        let mut ab_loop = AbLoop::default();
+        assert_eq!(ab_loop.region(), None);
+        ab_loop.set_start(secs(10));
+        assert_eq!(ab_loop.region(), None);
+        ab_loop.set_end(secs(20));
+        assert_eq!(ab_loop.region(), Some((secs(10), secs(20))));
+    }
+
+    #[test]
+    fn end_before_start_swaps_them() {
+        let mut ab_loop = AbLoop::default();
+        ab_loop.set_start(secs(30));
+        ab_loop.set_end(secs(12));
+        assert_eq!(ab_loop.region(), Some((secs(12), secs(30))));
+        // B no mesmo ponto do A não faz nada
+        ab_loop.set_end(secs(12));
+        assert_eq!(ab_loop.region(), Some((secs(12), secs(30))));
+    }
+
+    #[test]
+    fn end_without_start_loops_from_the_beginning() {
+        let mut ab_loop = AbLoop::default();
+        ab_loop.set_end(secs(45));
+        assert_eq!(ab_loop.region(), Some((Duration::ZERO, secs(45))));
+    }
+
+    #[test]
+    fn start_after_end_drops_the_end() {
+        let mut ab_loop = AbLoop::default();
+        ab_loop.set_start(secs(10));
+        ab_loop.set_end(secs(20));
+        ab_loop.set_start(secs(15));
+        assert_eq!(ab_loop.region(), Some((secs(15), secs(20))));
+        ab_loop.set_start(secs(20));
+        assert_eq!(ab_loop, AbLoop { start: Some(secs(20)), end: None });
+        assert_eq!(ab_loop.region(), None);
+    }
+}
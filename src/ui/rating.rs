@@ -0,0 +1,36 @@
+// src/ui/rating.rs
+// Estrelas (0–5) e o coração de "loved", usados na tabela da playlist e no painel da
+// faixa atual. Os widgets só devolvem o novo valor; quem chama grava no banco.
+
+use eframe::egui;
+
+pub const MAX_RATING: u8 = 5;
+
+const STAR_COLOR: egui::Color32 = egui::Color32::from_rgb(240, 180, 40);
+const LOVED_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 60, 80);
+
+pub fn stars_text(rating: u8) -> String {
+    if rating == 0 { return String::new(); }
+    (1..=MAX_RATING).map(|n| if n <= rating { '★' } else { '☆' }).collect()
+}
+
+// Clique na n-ésima estrela dá nota n; clicar na nota atual limpa a avaliação
+pub fn stars(ui: &mut egui::Ui, rating: u8) -> Option<u8> {
+    let mut new_rating = None;
+    ui.spacing_mut().item_spacing.x = 0.0;
+    for n in 1..=MAX_RATING {
+        let text = if n <= rating { egui::RichText::new("★").color(STAR_COLOR) } else { egui::RichText::new("☆").weak() };
+        let response = ui.add(egui::Label::new(text).sense(egui::Sense::click()).selectable(false));
+        if response.clicked() { new_rating = Some(if n == rating { 0 } else { n }); }
+        response.on_hover_text(if n == rating { "Clear rating".to_string() } else { format!("Rate {} star(s)", n) });
+    }
+    new_rating
+}
+
+pub fn loved(ui: &mut egui::Ui, loved: bool) -> Option<bool> {
+    let text = if loved { egui::RichText::new("♥").color(LOVED_COLOR) } else { egui::RichText::new("♡").weak() };
+    let response = ui.add(egui::Label::new(text).sense(egui::Sense::click()).selectable(false));
+    let clicked = response.clicked();
+    response.on_hover_text(if loved { "Unlove" } else { "Love" });
+    if clicked { Some(!loved) } else { None }
+}
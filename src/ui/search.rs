@@ -0,0 +1,38 @@
+// src/ui/search.rs
+// Busca "fuzzy" simples: os caracteres da consulta precisam aparecer em ordem no texto,
+// não necessariamente juntos. Sequências contíguas e inícios de palavra valem mais pontos.
+
+// Pontua `text` para a consulta `query`. Retorna None se não houver correspondência.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query.chars().flat_map(|c| c.to_lowercase()).filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() { return Some(0); }
+
+    let text: Vec<char> = text.chars().flat_map(|c| c.to_lowercase()).collect();
+    let mut score = 0;
+    let mut query_pos = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for (text_pos, &c) in text.iter().enumerate() {
+        if query_pos >= query.len() { break; }
+        if c != query[query_pos] { continue; }
+
+        score += 1;
+        if previous_match.is_some_and(|prev| prev + 1 == text_pos) { score += 5; }
+        let at_word_start = text_pos == 0 || !text[text_pos - 1].is_alphanumeric();
+        if at_word_start { score += 3; }
+        previous_match = Some(text_pos);
+        query_pos += 1;
+    }
+
+    if query_pos == query.len() { Some(score) } else { None }
+}
+
+// Cada palavra da consulta precisa casar com algum dos campos (nome do arquivo, título, artista...).
+pub fn matches_all_terms(query: &str, fields: &[&str]) -> Option<i32> {
+    let mut total = 0;
+    for term in query.split_whitespace() {
+        let best = fields.iter().filter_map(|field| fuzzy_score(term, field)).max()?;
+        total += best;
+    }
+    Some(total)
+}
@@ -0,0 +1,303 @@
+// src/ui/shuffle.rs
+// Ordem aleatória da playlist em reprodução: uma permutação gerada uma vez por volta,
+// para nenhuma faixa repetir antes de todas tocarem, e o histórico do que foi ouvido
+// para o "Previous" voltar de verdade.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// Limite do histórico (o mais antigo é descartado)
+const HISTORY_LIMIT: usize = 500;
+
+// --- Modos de shuffle ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShuffleMode {
+    Off,
+    Tracks,       // Faixas em ordem aleatória
+    Albums,       // Álbuns em ordem aleatória, faixas de cada álbum em ordem
+    Weighted,     // Faixas bem avaliadas tendem a vir antes, as mais puladas para o fim
+    ArtistSpread, // Como Tracks, mas evita o mesmo artista duas vezes seguidas
+}
+
+impl ShuffleMode {
+    pub const ALL: [ShuffleMode; 5] = [ShuffleMode::Off, ShuffleMode::Tracks, ShuffleMode::Albums, ShuffleMode::Weighted, ShuffleMode::ArtistSpread];
+
+    // Ciclo do botão
+    pub fn next(&self) -> Self {
+        match self {
+            ShuffleMode::Off => ShuffleMode::Tracks,
+            ShuffleMode::Tracks => ShuffleMode::Albums,
+            ShuffleMode::Albums => ShuffleMode::Weighted,
+            ShuffleMode::Weighted => ShuffleMode::ArtistSpread,
+            ShuffleMode::ArtistSpread => ShuffleMode::Off,
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ShuffleMode::Off => "🔀 OFF",
+            ShuffleMode::Tracks => "🔀 Tracks",
+            ShuffleMode::Albums => "🔀 Albums",
+            ShuffleMode::Weighted => "🔀 Weighted",
+            ShuffleMode::ArtistSpread => "🔀 Artists",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ShuffleMode::Off => "Play in playlist order",
+            ShuffleMode::Tracks => "Shuffle tracks",
+            ShuffleMode::Albums => "Shuffle albums, keeping track order inside each album",
+            ShuffleMode::Weighted => "Shuffle favoring highly rated and rarely skipped tracks",
+            ShuffleMode::ArtistSpread => "Shuffle tracks, never the same artist twice in a row",
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        *self != ShuffleMode::Off
+    }
+
+    // Modos que usam as tags (álbum/artista) de todas as faixas
+    pub fn needs_tags(&self) -> bool {
+        matches!(self, ShuffleMode::Albums | ShuffleMode::ArtistSpread)
+    }
+}
+
+// Dados de cada faixa da playlist usados pelos modos (mesma ordem de `tracks`)
+pub struct ShuffleCandidate {
+    pub album: String, // Álbum das tags ou, na falta, a pasta do arquivo
+    pub artist: Option<String>,
+    pub weight: f64,   // > 0; maior = tende a tocar antes
+}
+
+#[derive(Default)]
+pub struct ShuffleOrder {
+    playlist_id: u64,
+    mode: Option<ShuffleMode>,
+    // Assinatura das faixas quando a ordem foi gerada; se a playlist mudar a ordem é refeita
+    signature: u64,
+    // Faixas que ainda não tocaram nesta volta; a próxima fica no fim
+    upcoming: Vec<usize>,
+    played: HashSet<PathBuf>,
+    history: Vec<PathBuf>,
+}
+
+impl ShuffleOrder {
+    // Começa uma volta nova (ex.: ao ligar o shuffle); a faixa atual conta como tocada
+    pub fn start_cycle(&mut self, current: Option<&Path>) {
+        self.signature = 0;
+        self.upcoming.clear();
+        self.played = current.map(Path::to_path_buf).into_iter().collect();
+    }
+
+    // Registra a faixa que começou a tocar
+    pub fn record(&mut self, playlist_id: u64, path: &Path) {
+        if playlist_id != self.playlist_id {
+            // Outra playlist: histórico e volta não valem mais
+            *self = ShuffleOrder { playlist_id, ..ShuffleOrder::default() };
+        }
+        self.played.insert(path.to_path_buf());
+        if self.history.last().map(PathBuf::as_path) != Some(path) {
+            self.history.push(path.to_path_buf());
+            if self.history.len() > HISTORY_LIMIT { self.history.remove(0); }
+        }
+    }
+
+    // Próxima faixa da permutação. Ao fim da volta, só recomeça se `wrap` (RepeatMode::Playlist).
+    pub fn next(&mut self, playlist_id: u64, tracks: &[PathBuf], candidates: &[ShuffleCandidate], mode: ShuffleMode, wrap: bool) -> Option<usize> {
+        if tracks.is_empty() || candidates.len() != tracks.len() { return None; }
+        if playlist_id != self.playlist_id {
+            *self = ShuffleOrder { playlist_id, ..ShuffleOrder::default() };
+        }
+        let signature = tracks_signature(tracks, candidates, mode);
+        if signature != self.signature || self.mode != Some(mode) {
+            self.signature = signature;
+            self.mode = Some(mode);
+            self.regenerate(tracks, candidates, mode);
+        }
+        if let Some(index) = self.pop_unplayed(tracks, candidates, mode) { return Some(index); }
+        if !wrap { return None; }
+
+        self.played.clear();
+        self.regenerate(tracks, candidates, mode);
+        // Evita repetir na virada a faixa que acabou de tocar
+        let last_played = self.history.last();
+        if mode != ShuffleMode::Albums && self.upcoming.len() > 1 && self.upcoming.last().map(|&i| &tracks[i]) == last_played {
+            let last = self.upcoming.len() - 1;
+            self.upcoming.swap(0, last);
+        }
+        self.pop_unplayed(tracks, candidates, mode)
+    }
+
+    // Faixa ouvida antes da atual; a atual sai do histórico
+    pub fn previous(&mut self) -> Option<PathBuf> {
+        self.history.pop();
+        self.history.last().cloned()
+    }
+
+    // Arquivo movido pelo organizador: continua contando como tocado
+    pub fn rename_path(&mut self, from: &Path, to: &Path) {
+        if self.played.remove(from) { self.played.insert(to.to_path_buf()); }
+        for path in self.history.iter_mut().filter(|path| path.as_path() == from) { *path = to.to_path_buf(); }
+    }
+
+    // Monta a ordem da volta (a próxima faixa fica no fim de `upcoming`)
+    fn regenerate(&mut self, tracks: &[PathBuf], candidates: &[ShuffleCandidate], mode: ShuffleMode) {
+        let mut rng = rand::thread_rng();
+        let unplayed: Vec<usize> = (0..tracks.len()).filter(|&i| !self.played.contains(&tracks[i])).collect();
+        let mut order = match mode {
+            ShuffleMode::Albums => {
+                // Álbuns na ordem em que aparecem, depois embaralhados como blocos
+                let mut albums: Vec<Vec<usize>> = Vec::new();
+                let mut album_slot: HashMap<&str, usize> = HashMap::new();
+                for &index in &unplayed {
+                    let slot = *album_slot.entry(candidates[index].album.as_str()).or_insert_with(|| { albums.push(Vec::new()); albums.len() - 1 });
+                    albums[slot].push(index);
+                }
+                albums.shuffle(&mut rng);
+                albums.concat()
+            }
+            ShuffleMode::Weighted => {
+                // Amostragem ponderada sem reposição (Efraimidis-Spirakis): chave = u^(1/peso)
+                let mut keyed: Vec<(f64, usize)> = unplayed.iter()
+                    .map(|&index| (rng.gen::<f64>().powf(1.0 / candidates[index].weight.max(f64::EPSILON)), index))
+                    .collect();
+                keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+                keyed.into_iter().map(|(_, index)| index).collect()
+            }
+            _ => {
+                let mut order = unplayed;
+                order.shuffle(&mut rng);
+                order
+            }
+        };
+        order.reverse();
+        self.upcoming = order;
+    }
+
+    // Faixas tocadas fora da ordem (clique na lista, fila) são puladas
+    fn pop_unplayed(&mut self, tracks: &[PathBuf], candidates: &[ShuffleCandidate], mode: ShuffleMode) -> Option<usize> {
+        self.upcoming.retain(|&index| tracks.get(index).is_some_and(|path| !self.played.contains(path)));
+        if mode == ShuffleMode::ArtistSpread {
+            // A primeira faixa (a partir do fim) de outro artista; se não houver, qualquer uma
+            let last_artist = self.history.last()
+                .and_then(|last| tracks.iter().position(|path| path == last))
+                .and_then(|index| candidates[index].artist.as_deref());
+            if let Some(last_artist) = last_artist {
+                let different = self.upcoming.iter().rposition(|&index| candidates[index].artist.as_deref() != Some(last_artist));
+                if let Some(position) = different { return Some(self.upcoming.remove(position)); }
+            }
+        }
+        self.upcoming.pop()
+    }
+}
+
+// No modo Albums os álbuns também entram: tags lidas em segundo plano depois do início da
+// volta refazem os blocos das faixas que ainda não tocaram
+fn tracks_signature(tracks: &[PathBuf], candidates: &[ShuffleCandidate], mode: ShuffleMode) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tracks.hash(&mut hasher);
+    if mode == ShuffleMode::Albums {
+        for candidate in candidates { candidate.album.hash(&mut hasher); }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracks(count: usize) -> Vec<PathBuf> {
+        (0..count).map(|i| PathBuf::from(format!("/music/{:02}.mp3", i))).collect()
+    }
+
+    fn candidate(album: &str, artist: &str) -> ShuffleCandidate {
+        ShuffleCandidate { album: album.to_string(), artist: Some(artist.to_string()), weight: 1.0 }
+    }
+
+    // Toca a volta inteira como o player faz: pede a próxima e registra
+    fn play_cycle(order: &mut ShuffleOrder, tracks: &[PathBuf], candidates: &[ShuffleCandidate], mode: ShuffleMode, wrap: bool) -> Vec<usize> {
+        let mut played = Vec::new();
+        while played.len() < tracks.len() {
+            let Some(index) = order.next(1, tracks, candidates, mode, wrap) else { break; };
+            order.record(1, &tracks[index]);
+            played.push(index);
+        }
+        played
+    }
+
+    #[test]
+    fn visits_every_track_once_per_cycle() {
+        let tracks = tracks(20);
+        let candidates: Vec<ShuffleCandidate> = (0..20).map(|_| candidate("Album", "Artist")).collect();
+        let mut order = ShuffleOrder::default();
+        let mut first = play_cycle(&mut order, &tracks, &candidates, ShuffleMode::Tracks, false);
+        let last_of_first = *first.last().unwrap();
+        first.sort_unstable();
+        assert_eq!(first, (0..20).collect::<Vec<_>>());
+        // Sem repetir a playlist a volta termina
+        assert_eq!(order.next(1, &tracks, &candidates, ShuffleMode::Tracks, false), None);
+
+        // Repetindo, começa outra volta completa sem tocar de novo a última faixa logo de cara
+        let mut second = play_cycle(&mut order, &tracks, &candidates, ShuffleMode::Tracks, true);
+        assert_ne!(second[0], last_of_first);
+        second.sort_unstable();
+        assert_eq!(second, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn previous_walks_back_without_disturbing_the_cycle() {
+        let tracks = tracks(6);
+        let candidates: Vec<ShuffleCandidate> = (0..6).map(|_| candidate("Album", "Artist")).collect();
+        let mut order = ShuffleOrder::default();
+        let played: Vec<usize> = (0..3).map(|_| {
+            let index = order.next(1, &tracks, &candidates, ShuffleMode::Tracks, false).unwrap();
+            order.record(1, &tracks[index]);
+            index
+        }).collect();
+
+        assert_eq!(order.previous(), Some(tracks[played[1]].clone()));
+        order.record(1, &tracks[played[1]]);
+        assert_eq!(order.previous(), Some(tracks[played[0]].clone()));
+        order.record(1, &tracks[played[0]]);
+
+        // Voltar não devolve faixas à volta: as três restantes saem, uma vez cada
+        let mut rest = play_cycle(&mut order, &tracks, &candidates, ShuffleMode::Tracks, false);
+        rest.extend(&played);
+        rest.sort_unstable();
+        assert_eq!(rest, (0..6).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn keeps_albums_together_in_track_order() {
+        let tracks = tracks(9);
+        let candidates: Vec<ShuffleCandidate> = (0..9).map(|i| candidate(["A", "B", "C"][i / 3], "Artist")).collect();
+        let mut order = ShuffleOrder::default();
+        let played = play_cycle(&mut order, &tracks, &candidates, ShuffleMode::Albums, false);
+        assert_eq!(played.len(), 9);
+        for block in played.chunks(3) {
+            assert_eq!(block[0] % 3, 0);
+            assert_eq!(block, [block[0], block[0] + 1, block[0] + 2]);
+        }
+    }
+
+    #[test]
+    fn spreads_artists_when_possible() {
+        let tracks = tracks(10);
+        let candidates: Vec<ShuffleCandidate> = (0..10).map(|i| candidate("Album", if i % 2 == 0 { "Even" } else { "Odd" })).collect();
+        for _ in 0..20 {
+            let mut order = ShuffleOrder::default();
+            let played = play_cycle(&mut order, &tracks, &candidates, ShuffleMode::ArtistSpread, false);
+            assert_eq!(played.len(), 10);
+            for pair in played.windows(2) {
+                assert_ne!(candidates[pair[0]].artist, candidates[pair[1]].artist, "{:?}", played);
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+// src/ui/smart_playlist.rs
+// Playlists inteligentes: em vez de uma lista fixa, a aba guarda regras (gênero, nota,
+// quando tocou...) e as faixas são recalculadas sempre que a biblioteca muda. As faixas
+// candidatas são as de todas as playlists comuns mais as que já estão no banco do player.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::library::TrackStats;
+use crate::metadata::TrackInfo;
+use super::rating;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// --- Uma regra (todas precisam valer: E lógico) ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SmartRule {
+    GenreIs(String),
+    ArtistIs(String),
+    AlbumIs(String),
+    RatingAtLeast(u8),
+    Loved,
+    NotPlayedInDays(u32), // Faixas nunca tocadas também valem
+    PlayedAtLeast(u32),
+}
+
+impl SmartRule {
+    // Modelos oferecidos no editor ao adicionar/trocar o tipo da regra
+    const TEMPLATES: [SmartRule; 7] = [
+        SmartRule::GenreIs(String::new()),
+        SmartRule::ArtistIs(String::new()),
+        SmartRule::AlbumIs(String::new()),
+        SmartRule::RatingAtLeast(4),
+        SmartRule::Loved,
+        SmartRule::NotPlayedInDays(30),
+        SmartRule::PlayedAtLeast(1),
+    ];
+
+    fn kind_label(&self) -> &'static str {
+        match self {
+            SmartRule::GenreIs(_) => "Genre is",
+            SmartRule::ArtistIs(_) => "Artist is",
+            SmartRule::AlbumIs(_) => "Album is",
+            SmartRule::RatingAtLeast(_) => "Rating at least",
+            SmartRule::Loved => "Is loved",
+            SmartRule::NotPlayedInDays(_) => "Not played in (days)",
+            SmartRule::PlayedAtLeast(_) => "Play count at least",
+        }
+    }
+
+    // Regras que dependem das tags (as faixas precisam ter os metadados lidos)
+    fn needs_tags(&self) -> bool {
+        matches!(self, SmartRule::GenreIs(_) | SmartRule::ArtistIs(_) | SmartRule::AlbumIs(_))
+    }
+
+    fn matches(&self, info: Option<&TrackInfo>, stats: Option<&TrackStats>, now: i64) -> bool {
+        match self {
+            SmartRule::GenreIs(genre) => info.and_then(|i| i.genre.as_deref()).is_some_and(|value| {
+                // Tags com vários gêneros: "Jazz; Fusion", "Jazz/Fusion"
+                value.split([';', '/', ',']).any(|part| part.trim().eq_ignore_ascii_case(genre.trim()))
+            }),
+            SmartRule::ArtistIs(artist) => info.and_then(|i| i.artist.as_deref()).is_some_and(|value| value.trim().eq_ignore_ascii_case(artist.trim())),
+            SmartRule::AlbumIs(album) => info.and_then(|i| i.album.as_deref()).is_some_and(|value| value.trim().eq_ignore_ascii_case(album.trim())),
+            SmartRule::RatingAtLeast(min) => stats.map_or(0, |s| s.rating) >= *min,
+            SmartRule::Loved => stats.is_some_and(|s| s.loved),
+            SmartRule::NotPlayedInDays(days) => match stats.and_then(|s| s.last_played) {
+                Some(last_played) => now - last_played >= *days as i64 * SECONDS_PER_DAY,
+                None => true,
+            },
+            SmartRule::PlayedAtLeast(plays) => stats.map_or(0, |s| s.plays) >= *plays,
+        }
+    }
+}
+
+// --- Ordem das faixas ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SmartOrder {
+    Random,
+    HighestRated,
+    MostPlayed,
+    LeastRecentlyPlayed,
+}
+
+impl SmartOrder {
+    const ALL: [SmartOrder; 4] = [SmartOrder::Random, SmartOrder::HighestRated, SmartOrder::MostPlayed, SmartOrder::LeastRecentlyPlayed];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SmartOrder::Random => "Random",
+            SmartOrder::HighestRated => "Highest rated",
+            SmartOrder::MostPlayed => "Most played",
+            SmartOrder::LeastRecentlyPlayed => "Least recently played",
+        }
+    }
+}
+
+// --- Definição salva junto com a aba ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmartRules {
+    pub rules: Vec<SmartRule>,
+    pub limit: Option<usize>,
+    pub order: SmartOrder,
+}
+
+impl Default for SmartRules {
+    fn default() -> Self {
+        SmartRules { rules: vec![SmartRule::RatingAtLeast(4)], limit: Some(50), order: SmartOrder::Random }
+    }
+}
+
+// Faixa candidata com os dados que as regras consultam
+pub struct SmartCandidate<'a> {
+    pub path: &'a Path,
+    pub info: Option<&'a TrackInfo>,
+    pub stats: Option<&'a TrackStats>,
+}
+
+impl SmartRules {
+    pub fn needs_tags(&self) -> bool {
+        self.rules.iter().any(SmartRule::needs_tags)
+    }
+
+    // Faixas que atendem às regras, ordenadas e limitadas. Na ordem aleatória as faixas
+    // que já estavam na aba (`previous`) mantêm a posição, para a lista não se embaralhar
+    // de novo a cada mudança na biblioteca; as novas entram sorteadas no fim.
+    pub fn evaluate(&self, candidates: &[SmartCandidate<'_>], previous: &[PathBuf], now: i64) -> Vec<PathBuf> {
+        let mut matching: Vec<&SmartCandidate<'_>> = candidates.iter()
+            .filter(|c| self.rules.iter().all(|rule| rule.matches(c.info, c.stats, now)))
+            .collect();
+        match self.order {
+            SmartOrder::Random => {
+                let previous_position: HashMap<&Path, usize> = previous.iter().enumerate().map(|(index, path)| (path.as_path(), index)).collect();
+                let position = |path: &Path| previous_position.get(path).copied();
+                let (mut kept, mut added): (Vec<_>, Vec<_>) = matching.into_iter().partition(|c| position(c.path).is_some());
+                kept.sort_by_key(|c| position(c.path));
+                added.shuffle(&mut rand::thread_rng());
+                kept.extend(added);
+                matching = kept;
+            }
+            SmartOrder::HighestRated => matching.sort_by_key(|c| std::cmp::Reverse(c.stats.map_or(0, |s| s.rating))),
+            SmartOrder::MostPlayed => matching.sort_by_key(|c| std::cmp::Reverse(c.stats.map_or(0, |s| s.plays))),
+            SmartOrder::LeastRecentlyPlayed => matching.sort_by_key(|c| c.stats.and_then(|s| s.last_played).unwrap_or(i64::MIN)),
+        }
+        if let Some(limit) = self.limit { matching.truncate(limit); }
+        matching.into_iter().map(|c| c.path.to_path_buf()).collect()
+    }
+
+    // Resumo de uma linha (dica da aba)
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = self.rules.iter().map(|rule| match rule {
+            SmartRule::GenreIs(value) | SmartRule::ArtistIs(value) | SmartRule::AlbumIs(value) => format!("{} \"{}\"", rule.kind_label(), value),
+            SmartRule::RatingAtLeast(min) => format!("{} {}", rule.kind_label(), rating::stars_text(*min)),
+            SmartRule::Loved => rule.kind_label().to_string(),
+            SmartRule::NotPlayedInDays(n) => format!("Not played in {} days", n),
+            SmartRule::PlayedAtLeast(n) => format!("{} {}", rule.kind_label(), n),
+        }).collect();
+        if parts.is_empty() { parts.push("All tracks".to_string()); }
+        let limit = self.limit.map(|n| format!(", limit {}", n)).unwrap_or_default();
+        format!("{}{}, order: {}", parts.join(" AND "), limit, self.order.label().to_lowercase())
+    }
+}
+
+// Caminhos únicos, na ordem em que aparecem
+pub fn unique_paths<'a>(paths: impl Iterator<Item = &'a PathBuf>) -> Vec<&'a PathBuf> {
+    let mut seen = HashSet::new();
+    paths.filter(|path| seen.insert(*path)).collect()
+}
+
+// --- Editor das regras (usado pela janela de playlist inteligente) ---
+pub fn rules_editor(ui: &mut egui::Ui, smart: &mut SmartRules) {
+    let mut remove: Option<usize> = None;
+    egui::Grid::new("smart_rules_grid").num_columns(3).spacing([8.0, 6.0]).show(ui, |ui| {
+        for (index, rule) in smart.rules.iter_mut().enumerate() {
+            egui::ComboBox::from_id_source(("smart_rule_kind", index)).selected_text(rule.kind_label()).show_ui(ui, |ui| {
+                for template in SmartRule::TEMPLATES {
+                    let selected = std::mem::discriminant(rule) == std::mem::discriminant(&template);
+                    if ui.selectable_label(selected, template.kind_label()).clicked() && !selected { *rule = template; }
+                }
+            });
+            match rule {
+                SmartRule::GenreIs(value) | SmartRule::ArtistIs(value) | SmartRule::AlbumIs(value) => {
+                    ui.add(egui::TextEdit::singleline(value).desired_width(160.0));
+                }
+                SmartRule::RatingAtLeast(min) => {
+                    ui.horizontal(|ui| {
+                        if let Some(new_min) = rating::stars(ui, *min) { *min = new_min; }
+                    });
+                }
+                SmartRule::Loved => { ui.label(""); }
+                SmartRule::NotPlayedInDays(days) => { ui.add(egui::DragValue::new(days).clamp_range(1..=3650)); }
+                SmartRule::PlayedAtLeast(plays) => { ui.add(egui::DragValue::new(plays).clamp_range(0..=10000)); }
+            }
+            if ui.small_button("✖").on_hover_text("Remove rule").clicked() { remove = Some(index); }
+            ui.end_row();
+        }
+    });
+    if let Some(index) = remove { smart.rules.remove(index); }
+    if ui.button("➕ Add rule").clicked() { smart.rules.push(SmartRule::GenreIs(String::new())); }
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        let mut limited = smart.limit.is_some();
+        if ui.checkbox(&mut limited, "Limit to").changed() { smart.limit = limited.then_some(50); }
+        if let Some(limit) = &mut smart.limit { ui.add(egui::DragValue::new(limit).clamp_range(1..=10000)); ui.label("tracks"); }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Order:");
+        egui::ComboBox::from_id_source("smart_order").selected_text(smart.order.label()).show_ui(ui, |ui| {
+            for order in SmartOrder::ALL { ui.selectable_value(&mut smart.order, order, order.label()); }
+        });
+    });
+}
+
+// Estado da janela de criação/edição
+pub struct SmartEditor {
+    pub tab_id: Option<u64>, // None = nova playlist
+    pub name: String,
+    pub rules: SmartRules,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    struct Track {
+        path: PathBuf,
+        info: TrackInfo,
+        stats: TrackStats,
+    }
+
+    fn track(name: &str, genre: &str, rating: u8, plays: u32, days_ago: Option<i64>) -> Track {
+        Track {
+            path: PathBuf::from(format!("/music/{}.mp3", name)),
+            info: TrackInfo { genre: Some(genre.to_string()), ..TrackInfo::default() },
+            stats: TrackStats { rating, plays, last_played: days_ago.map(|days| NOW - days * SECONDS_PER_DAY), ..TrackStats::default() },
+        }
+    }
+
+    fn library() -> Vec<Track> {
+        vec![
+            track("a", "Jazz", 5, 10, Some(40)),
+            track("b", "Jazz; Fusion", 4, 3, None),
+            track("c", "Jazz", 4, 7, Some(5)),
+            track("d", "Rock", 5, 1, Some(100)),
+            track("e", "jazz", 3, 2, Some(60)),
+            track("f", "Jazz/Bebop", 4, 20, Some(30)),
+        ]
+    }
+
+    fn candidates(tracks: &[Track]) -> Vec<SmartCandidate<'_>> {
+        tracks.iter().map(|t| SmartCandidate { path: &t.path, info: Some(&t.info), stats: Some(&t.stats) }).collect()
+    }
+
+    fn names(paths: &[PathBuf]) -> Vec<String> {
+        paths.iter().map(|path| path.file_stem().unwrap().to_string_lossy().into_owned()).collect()
+    }
+
+    fn rules(rules: Vec<SmartRule>, limit: Option<usize>, order: SmartOrder) -> SmartRules {
+        SmartRules { rules, limit, order }
+    }
+
+    #[test]
+    fn matches_genre_rating_and_days_not_played() {
+        let tracks = library();
+        let candidates = candidates(&tracks);
+        let check = |rule: SmartRule| names(&rules(vec![rule], None, SmartOrder::MostPlayed).evaluate(&candidates, &[], NOW));
+        assert_eq!(check(SmartRule::GenreIs("jazz".to_string())), ["f", "a", "c", "b", "e"]);
+        assert_eq!(check(SmartRule::RatingAtLeast(5)), ["a", "d"]);
+        // Nunca tocada conta como "não tocada"; exatamente 30 dias também
+        assert_eq!(check(SmartRule::NotPlayedInDays(30)), ["f", "a", "b", "e", "d"]);
+        // Sem estatísticas: nota 0 e nunca tocada
+        let unknown = PathBuf::from("/music/new.mp3");
+        let fresh = [SmartCandidate { path: &unknown, info: None, stats: None }];
+        assert!(rules(vec![SmartRule::RatingAtLeast(1)], None, SmartOrder::Random).evaluate(&fresh, &[], NOW).is_empty());
+        assert_eq!(names(&rules(vec![SmartRule::NotPlayedInDays(1)], None, SmartOrder::Random).evaluate(&fresh, &[], NOW)), ["new"]);
+    }
+
+    #[test]
+    fn all_rules_must_match_and_limit_applies_after_ordering() {
+        let tracks = library();
+        let candidates = candidates(&tracks);
+        let jazz_rated_stale = vec![SmartRule::GenreIs("Jazz".to_string()), SmartRule::RatingAtLeast(4), SmartRule::NotPlayedInDays(30)];
+        let result = rules(jazz_rated_stale.clone(), None, SmartOrder::HighestRated).evaluate(&candidates, &[], NOW);
+        assert_eq!(names(&result), ["a", "b", "f"]);
+        let result = rules(jazz_rated_stale, Some(2), SmartOrder::LeastRecentlyPlayed).evaluate(&candidates, &[], NOW);
+        assert_eq!(names(&result), ["b", "a"]);
+        // Sem regras, todas as faixas
+        assert_eq!(rules(Vec::new(), None, SmartOrder::Random).evaluate(&candidates, &[], NOW).len(), tracks.len());
+    }
+
+    #[test]
+    fn random_order_keeps_previous_positions() {
+        let tracks = library();
+        let candidates = candidates(&tracks);
+        let smart = rules(vec![SmartRule::GenreIs("Jazz".to_string())], None, SmartOrder::Random);
+        let mut first = smart.evaluate(&candidates, &[], NOW);
+        let mut sorted = names(&first);
+        sorted.sort();
+        assert_eq!(sorted, ["a", "b", "c", "e", "f"]);
+        assert_eq!(smart.evaluate(&candidates, &first, NOW), first);
+
+        // Uma faixa que saiu da aba volta sorteada depois das que ficaram
+        let removed = first.remove(2);
+        let again = smart.evaluate(&candidates, &first, NOW);
+        assert_eq!(again[..4], first[..]);
+        assert_eq!(again[4], removed);
+    }
+}
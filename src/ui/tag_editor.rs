@@ -0,0 +1,177 @@
+// src/ui/tag_editor.rs
+// Janela do editor de tags para uma ou mais faixas. Com várias faixas, campos que têm
+// valores diferentes começam em "manter o existente" e só são gravados se forem editados.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use eframe::egui;
+use rfd::FileDialog;
+
+use crate::tag_writer::{CoverChange, TagChanges, TagFields};
+
+const FIELD_LABELS: [&str; 9] = ["Title", "Artist", "Album artist", "Album", "Track", "Disc", "Year", "Genre", "Comment"];
+const COVER_SIZE: f32 = 140.0;
+
+struct EditField {
+    value: String,
+    original: Option<String>, // None = valores diferentes entre as faixas
+    keep: bool,               // Manter o valor de cada faixa (não gravar o campo)
+}
+
+pub enum TagEditorAction {
+    Save(Box<TagChanges>),
+    Close,
+}
+
+pub struct TagEditor {
+    pub paths: Vec<PathBuf>,
+    fields: Vec<EditField>,
+    cover: Option<(String, Arc<[u8]>)>, // Capa exibida (uri para o cache de imagens do egui, bytes)
+    covers_differ: bool,
+    cover_change: CoverChange,
+    read_errors: Vec<String>,
+    skipped: usize, // Faixas deixadas de fora por erro de leitura
+}
+
+impl TagEditor {
+    pub fn open(paths: Vec<PathBuf>) -> Self {
+        let mut read_errors = Vec::new();
+        // Faixas que não puderam ser lidas ficam de fora: os valores "iguais em todas" não foram
+        // comparados com os delas, e gravar por cima apagaria o que elas têm de diferente
+        let (paths, tags): (Vec<PathBuf>, Vec<TagFields>) = paths.into_iter()
+            .filter_map(|path| match TagFields::read(&path) {
+                Ok(tags) => Some((path, tags)),
+                Err(e) => { read_errors.push(e); None }
+            })
+            .unzip();
+        let skipped = read_errors.len();
+        let values: Vec<[String; 9]> = tags.iter().map(field_values).collect();
+        let fields = (0..FIELD_LABELS.len()).map(|i| {
+            let first = values.first().map(|v| v[i].clone()).unwrap_or_default();
+            let same = values.iter().all(|v| v[i] == first);
+            if same { EditField { value: first.clone(), original: Some(first), keep: false } }
+            else { EditField { value: String::new(), original: None, keep: true } }
+        }).collect();
+        let first_cover = tags.first().and_then(|t| t.cover.clone());
+        let covers_differ = tags.iter().any(|t| t.cover != first_cover);
+        TagEditor {
+            paths,
+            fields,
+            cover: if covers_differ { None } else { first_cover.map(cover_entry) },
+            covers_differ,
+            cover_change: CoverChange::Keep,
+            read_errors,
+            skipped,
+        }
+    }
+
+    // Só os campos alterados entram na gravação
+    fn changes(&self) -> TagChanges {
+        let change = |i: usize| {
+            let field = &self.fields[i];
+            if field.keep || field.original.as_deref() == Some(field.value.as_str()) { None } else { Some(field.value.clone()) }
+        };
+        TagChanges {
+            title: change(0),
+            artist: change(1),
+            album_artist: change(2),
+            album: change(3),
+            track: change(4),
+            disc: change(5),
+            year: change(6),
+            genre: change(7),
+            comment: change(8),
+            cover: self.cover_change.clone(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<TagEditorAction> {
+        let mut open = true;
+        let mut action = None;
+        let title = if self.paths.len() == 1 { "Edit tags".to_string() } else { format!("Edit tags ({} tracks)", self.paths.len()) };
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                for error in &self.read_errors { ui.colored_label(egui::Color32::RED, error); }
+                if self.skipped > 0 { ui.weak(format!("{} track(s) could not be read; they are left out and won't be changed.", self.skipped)); }
+                ui.horizontal_top(|ui| {
+                    egui::Grid::new("tag_editor_grid").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+                        for (label, field) in FIELD_LABELS.iter().zip(&mut self.fields) {
+                            ui.label(*label);
+                            ui.horizontal(|ui| {
+                                let hint = if field.keep { "(keep existing)" } else { "" };
+                                let edit = ui.add(egui::TextEdit::singleline(&mut field.value).hint_text(hint).desired_width(220.0));
+                                if edit.changed() { field.keep = false; }
+                                if field.original.is_none() && !field.keep && ui.small_button("↺").on_hover_text("Keep each track's value").clicked() {
+                                    field.keep = true;
+                                    field.value.clear();
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                    ui.separator();
+                    ui.vertical(|ui| self.show_cover(ui));
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!self.paths.is_empty(), egui::Button::new("💾 Save")).clicked() { action = Some(TagEditorAction::Save(Box::new(self.changes()))); }
+                    if ui.button("Cancel").clicked() { action = Some(TagEditorAction::Close); }
+                });
+            });
+        if !open { action = Some(TagEditorAction::Close); }
+        action
+    }
+
+    fn show_cover(&mut self, ui: &mut egui::Ui) {
+        ui.label("Cover art");
+        match &self.cover {
+            Some((uri, bytes)) => {
+                ui.add(egui::Image::from_bytes(uri.clone(), egui::load::Bytes::Shared(bytes.clone()))
+                    .fit_to_exact_size(egui::vec2(COVER_SIZE, COVER_SIZE)));
+            }
+            None => {
+                let text = if self.covers_differ && matches!(self.cover_change, CoverChange::Keep) { "(different covers)" } else { "(no cover)" };
+                ui.add_sized([COVER_SIZE, COVER_SIZE], egui::Label::new(egui::RichText::new(text).weak()));
+            }
+        }
+        ui.horizontal(|ui| {
+            let replace_label = if self.cover.is_some() { "Replace..." } else { "Add..." };
+            if ui.button(replace_label).clicked() {
+                if let Some(path) = FileDialog::new().add_filter("Images", &["jpg", "jpeg", "png"]).pick_file() {
+                    match std::fs::read(&path) {
+                        Ok(data) => {
+                            self.cover = Some(cover_entry(data.clone()));
+                            self.cover_change = CoverChange::Replace(data);
+                        }
+                        Err(e) => self.read_errors.push(format!("Failed to read {:?}: {}", path, e)),
+                    }
+                }
+            }
+            let can_remove = self.cover.is_some() || self.covers_differ;
+            if ui.add_enabled(can_remove, egui::Button::new("Remove")).clicked() {
+                self.cover = None;
+                self.cover_change = CoverChange::Remove;
+            }
+        });
+    }
+}
+
+fn field_values(tags: &TagFields) -> [String; 9] {
+    [
+        tags.title.clone(), tags.artist.clone(), tags.album_artist.clone(), tags.album.clone(),
+        tags.track.clone(), tags.disc.clone(), tags.year.clone(), tags.genre.clone(), tags.comment.clone(),
+    ]
+}
+
+// A uri identifica a imagem no cache do egui; o hash evita mostrar uma capa antiga
+fn cover_entry(data: Vec<u8>) -> (String, Arc<[u8]>) {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    (format!("bytes://cover-{:016x}", hasher.finish()), data.into())
+}
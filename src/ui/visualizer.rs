@@ -0,0 +1,154 @@
+// src/ui/visualizer.rs
+// Painel do visualizador: espectro (FFT, eixo de frequência logarítmico, em barras ou linha)
+// e osciloscópio. Lê as amostras do SampleTap a cada frame; nada é calculado com o painel fechado.
+
+use std::sync::Arc;
+
+use eframe::egui;
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+use serde::{Deserialize, Serialize};
+
+use crate::sample_tap::SampleTap;
+
+const FFT_SIZE: usize = 4096;
+const SCOPE_SAMPLES: usize = 1024;
+const BANDS: usize = 64;
+const MIN_FREQ: f32 = 20.0;
+const MAX_FREQ: f32 = 20_000.0;
+const FLOOR_DB: f32 = -80.0;
+// Queda das barras por segundo (dB); a subida é imediata
+const FALL_DB_PER_SEC: f32 = 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpectrumStyle {
+    #[default]
+    Bars,
+    Line,
+}
+
+pub struct Visualizer {
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>, // Janela de Hann
+    input: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    bands: Vec<f32>, // dB suavizados
+    scope: Vec<f32>,
+}
+
+impl Default for Visualizer {
+    fn default() -> Self {
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+        let window = (0..FFT_SIZE).map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / FFT_SIZE as f32).cos()).collect();
+        Visualizer {
+            input: fft.make_input_vec(),
+            spectrum: fft.make_output_vec(),
+            scratch: fft.make_scratch_vec(),
+            fft,
+            window,
+            bands: vec![FLOOR_DB; BANDS],
+            scope: vec![0.0; SCOPE_SAMPLES],
+        }
+    }
+}
+
+impl Visualizer {
+    // Lê o tap e atualiza as bandas; `dt` é o tempo desde o último frame (para a queda)
+    fn analyze(&mut self, tap: &SampleTap, dt: f32) {
+        tap.read_latest(&mut self.input);
+        self.scope.copy_from_slice(&self.input[FFT_SIZE - SCOPE_SAMPLES..]);
+        for (sample, w) in self.input.iter_mut().zip(&self.window) { *sample *= w; }
+        if self.fft.process_with_scratch(&mut self.input, &mut self.spectrum, &mut self.scratch).is_err() { return; }
+
+        let rate = tap.sample_rate() as f32;
+        let bin_hz = rate / FFT_SIZE as f32;
+        let max_freq = MAX_FREQ.min(rate / 2.0);
+        // Normaliza para 0 dB com uma senoide cheia (soma da janela de Hann = N/2)
+        let scale = 2.0 / (FFT_SIZE as f32 * 0.5);
+        let fall = FALL_DB_PER_SEC * dt;
+        for (band, value) in self.bands.iter_mut().enumerate() {
+            let (low, high) = band_range(band, max_freq);
+            let first = ((low / bin_hz) as usize).max(1);
+            let last = ((high / bin_hz) as usize).max(first).min(self.spectrum.len() - 1);
+            let peak = self.spectrum[first..=last].iter().map(|c| c.norm() * scale).fold(0.0, f32::max);
+            let db = (20.0 * peak.max(1e-6).log10()).max(FLOOR_DB);
+            *value = if db > *value { db } else { (*value - fall).max(db) };
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, tap: &SampleTap, style: &mut SpectrumStyle) {
+        let dt = ui.input(|i| i.unstable_dt).min(0.1);
+        self.analyze(tap, dt);
+        ui.horizontal(|ui| {
+            ui.selectable_value(style, SpectrumStyle::Bars, "Bars");
+            ui.selectable_value(style, SpectrumStyle::Line, "Line");
+        });
+        let height = ui.available_height().max(60.0);
+        let width = ui.available_width();
+        ui.horizontal(|ui| {
+            let spectrum_size = egui::vec2(width * 0.65, height);
+            let (rect, _) = ui.allocate_exact_size(spectrum_size, egui::Sense::hover());
+            self.paint_spectrum(ui, rect, *style);
+            let scope_size = egui::vec2(ui.available_width(), height);
+            let (rect, _) = ui.allocate_exact_size(scope_size, egui::Sense::hover());
+            self.paint_scope(ui, rect);
+        });
+    }
+
+    fn paint_spectrum(&self, ui: &egui::Ui, rect: egui::Rect, style: SpectrumStyle) {
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+        let color = visuals.selection.bg_fill;
+        let level = |db: f32| ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+        let band_width = rect.width() / BANDS as f32;
+        match style {
+            SpectrumStyle::Bars => {
+                for (band, db) in self.bands.iter().enumerate() {
+                    let x = rect.left() + band as f32 * band_width;
+                    let top = rect.bottom() - level(*db) * rect.height();
+                    let bar = egui::Rect::from_min_max(egui::pos2(x + 1.0, top), egui::pos2(x + band_width - 1.0, rect.bottom()));
+                    painter.rect_filled(bar, 0.0, color);
+                }
+            }
+            SpectrumStyle::Line => {
+                let points = self.bands.iter().enumerate()
+                    .map(|(band, db)| egui::pos2(rect.left() + (band as f32 + 0.5) * band_width, rect.bottom() - level(*db) * rect.height()))
+                    .collect();
+                painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+            }
+        }
+        // Marcas de frequência no eixo logarítmico
+        let font = egui::FontId::proportional(10.0);
+        for (freq, label) in [(100.0, "100"), (1_000.0, "1k"), (10_000.0, "10k")] {
+            let x = rect.left() + freq_position(freq) * rect.width();
+            painter.vline(x, rect.y_range(), egui::Stroke::new(1.0, visuals.faint_bg_color));
+            painter.text(egui::pos2(x + 2.0, rect.top() + 2.0), egui::Align2::LEFT_TOP, label, font.clone(), visuals.weak_text_color());
+        }
+    }
+
+    fn paint_scope(&self, ui: &egui::Ui, rect: egui::Rect) {
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+        painter.hline(rect.x_range(), rect.center().y, egui::Stroke::new(1.0, visuals.faint_bg_color));
+        let step = rect.width() / (SCOPE_SAMPLES - 1) as f32;
+        let points = self.scope.iter().enumerate()
+            .map(|(i, sample)| egui::pos2(rect.left() + i as f32 * step, rect.center().y - sample.clamp(-1.0, 1.0) * rect.height() * 0.5))
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, visuals.text_color())));
+    }
+}
+
+// Posição (0–1) de uma frequência no eixo logarítmico
+fn freq_position(freq: f32) -> f32 {
+    (freq / MIN_FREQ).ln() / (MAX_FREQ / MIN_FREQ).ln()
+}
+
+// Faixa de frequências de uma banda, espaçadas em escala logarítmica
+fn band_range(band: usize, max_freq: f32) -> (f32, f32) {
+    let ratio = (MAX_FREQ / MIN_FREQ).powf(1.0 / BANDS as f32);
+    let low = MIN_FREQ * ratio.powi(band as i32);
+    (low.min(max_freq), (low * ratio).min(max_freq))
+}
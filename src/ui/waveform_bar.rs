@@ -0,0 +1,79 @@
+// src/ui/waveform_bar.rs
+// Barra de progresso desenhada como forma de onda. Clique pula direto; ao arrastar a
+// posição acompanha o ponteiro e o seek só é pedido ao soltar (cada seek recarrega o arquivo).
+// Enquanto os picos não ficam prontos, a barra é uma faixa lisa. Os pontos A e B do laço
+// aparecem como marcas, com o trecho entre eles destacado; os marcadores da faixa, como
+// pequenos triângulos na base.
+
+use eframe::egui;
+
+use crate::waveform::Peaks;
+
+const HEIGHT: f32 = 48.0;
+
+// Devolve a fração (0–1) escolhida para o seek; `loop_marks` são as frações de A e B
+pub fn show(ui: &mut egui::Ui, peaks: Option<&Peaks>, progress: f32, loop_marks: (Option<f32>, Option<f32>), bookmarks: &[f32], enabled: bool) -> (egui::Response, Option<f32>) {
+    let size = egui::vec2(ui.available_width(), HEIGHT);
+    let sense = if enabled { egui::Sense::click_and_drag() } else { egui::Sense::hover() };
+    let (rect, response) = ui.allocate_exact_size(size, sense);
+
+    let fraction_at = |pos: egui::Pos2| ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+    // No frame em que o arraste termina o egui pode não informar mais a posição da interação
+    let pointer = response.interact_pointer_pos().or_else(|| ui.ctx().pointer_latest_pos());
+    let mut seek = None;
+    let mut shown_progress = progress.clamp(0.0, 1.0);
+    if let Some(pos) = pointer {
+        if response.dragged() { shown_progress = fraction_at(pos); }
+        if response.clicked() || response.drag_stopped() { seek = Some(fraction_at(pos)); }
+    }
+
+    let visuals = ui.visuals();
+    let played_color = if enabled { visuals.selection.bg_fill } else { visuals.weak_text_color() };
+    let rest_color = visuals.widgets.inactive.bg_fill;
+    let painter = ui.painter_at(rect);
+    let split_x = rect.left() + shown_progress * rect.width();
+    let center = rect.center().y;
+    let mark_x = |fraction: f32| rect.left() + fraction.clamp(0.0, 1.0) * rect.width();
+    if let (Some(start), Some(end)) = loop_marks {
+        let region = egui::Rect::from_x_y_ranges(mark_x(start)..=mark_x(end), rect.y_range());
+        painter.rect_filled(region, 0.0, visuals.selection.bg_fill.gamma_multiply(0.2));
+    }
+    match peaks {
+        Some(peaks) => {
+            let width = rect.width().max(1.0) as usize;
+            for (column, (min, max)) in peaks.columns(width).into_iter().enumerate() {
+                let x = rect.left() + column as f32 + 0.5;
+                // Picos muito baixos ainda aparecem como um traço
+                let top = center - (max * rect.height() * 0.5).max(0.5);
+                let bottom = center - (min * rect.height() * 0.5).min(-0.5);
+                let color = if x <= split_x { played_color } else { rest_color };
+                painter.vline(x, top..=bottom, egui::Stroke::new(1.0, color));
+            }
+        }
+        None => {
+            let bar = egui::Rect::from_center_size(rect.center(), egui::vec2(rect.width(), 4.0));
+            painter.rect_filled(bar, 2.0, rest_color);
+            painter.rect_filled(egui::Rect::from_min_max(bar.min, egui::pos2(split_x, bar.max.y)), 2.0, played_color);
+        }
+    }
+    let font = egui::FontId::proportional(10.0);
+    for (mark, label) in [(loop_marks.0, "A"), (loop_marks.1, "B")] {
+        let Some(fraction) = mark else { continue; };
+        let x = mark_x(fraction);
+        painter.vline(x, rect.y_range(), egui::Stroke::new(1.0, visuals.selection.stroke.color));
+        painter.text(egui::pos2(x + 2.0, rect.top() + 1.0), egui::Align2::LEFT_TOP, label, font.clone(), visuals.selection.stroke.color);
+    }
+    for &fraction in bookmarks {
+        let x = mark_x(fraction);
+        let points = vec![egui::pos2(x - 4.0, rect.bottom()), egui::pos2(x + 4.0, rect.bottom()), egui::pos2(x, rect.bottom() - 6.0)];
+        painter.add(egui::Shape::convex_polygon(points, visuals.warn_fg_color, egui::Stroke::NONE));
+    }
+    painter.vline(split_x, rect.y_range(), egui::Stroke::new(1.5, visuals.strong_text_color()));
+    // Linha-guia na posição do ponteiro
+    if enabled {
+        if let Some(hover) = response.hover_pos().filter(|_| !response.dragged()) {
+            painter.vline(hover.x, rect.y_range(), egui::Stroke::new(1.0, visuals.weak_text_color()));
+        }
+    }
+    (response, seek)
+}
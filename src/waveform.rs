@@ -0,0 +1,176 @@
+// src/waveform.rs
+// Forma de onda da barra de progresso. Uma thread decodifica o arquivo inteiro com
+// load_symphonia_source e guarda o mínimo/máximo de cada bloco. O resultado vai para um
+// cache em disco com nome pelo hash do caminho, tamanho e data do arquivo, então só a primeira
+// vez custa.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crossbeam_channel::{unbounded, Receiver as CrossbeamReceiver, Sender as CrossbeamSender};
+use lofty::file::AudioFile;
+use rodio::Source;
+
+use crate::library::APP_NAME;
+use crate::load_symphonia_source;
+use crate::playlist_io::cue;
+
+pub const BUCKETS: usize = 2048;
+const CACHE_DIR: &str = "waveforms";
+const CACHE_MAGIC: &[u8; 4] = b"RMW1";
+
+// Mínimo e máximo de cada bloco, quantizados em -127..=127
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peaks {
+    pub buckets: Vec<(i8, i8)>,
+}
+
+impl Peaks {
+    // Reagrupa os blocos em `width` colunas (mínimo e máximo de cada coluna, em -1..=1)
+    pub fn columns(&self, width: usize) -> Vec<(f32, f32)> {
+        let len = self.buckets.len();
+        if len == 0 || width == 0 { return Vec::new(); }
+        (0..width).map(|column| {
+            let start = column * len / width;
+            let end = ((column + 1) * len / width).max(start + 1).min(len);
+            let (min, max) = self.buckets[start..end].iter().fold((0i8, 0i8), |(lo, hi), &(min, max)| (lo.min(min), hi.max(max)));
+            (min as f32 / 127.0, max as f32 / 127.0)
+        }).collect()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = CACHE_MAGIC.to_vec();
+        for &(min, max) in &self.buckets { bytes.extend([min as u8, max as u8]); }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Peaks> {
+        let data = bytes.strip_prefix(CACHE_MAGIC)?;
+        if data.len() % 2 != 0 { return None; }
+        Some(Peaks { buckets: data.chunks_exact(2).map(|pair| (pair[0] as i8, pair[1] as i8)).collect() })
+    }
+}
+
+// --- Cálculo ---
+fn compute(path: &Path, cancel: &AtomicBool) -> Result<Peaks, String> {
+    let (file_path, start, end) = match cue::read_track(path) {
+        Some(track) => { let track = track?; (track.file, track.start, track.end) }
+        None => (path.to_path_buf(), Duration::ZERO, None),
+    };
+    let key = cache_key(&file_path, start, end).map_err(|e| format!("Failed to read {:?}: {}", file_path, e))?;
+    if let Some(peaks) = load_cached(key) { return Ok(peaks); }
+
+    let file_duration = lofty::read_from_path(&file_path).map(|f| f.properties().duration()).map_err(|e| e.to_string())?;
+    let duration = end.unwrap_or(file_duration).saturating_sub(start);
+    let mut source = load_symphonia_source(&file_path)?;
+    if !start.is_zero() { source.try_seek(start).map_err(|e| format!("Seek failed: {}", e))?; }
+    if let Some(end) = end { source.set_end(end); }
+
+    let channels = source.channels().max(1) as usize;
+    let total_frames = (duration.as_secs_f64() * source.sample_rate() as f64).max(1.0);
+    let frames_per_bucket = (total_frames / BUCKETS as f64).max(1.0);
+    let mut buckets = vec![(0.0f32, 0.0f32); BUCKETS];
+    for (index, sample) in source.enumerate() {
+        // Confere o cancelamento de tempos em tempos (faixa trocada)
+        if index % 65_536 == 0 && cancel.load(Ordering::Relaxed) { return Err("Cancelled".to_string()); }
+        let bucket = ((index / channels) as f64 / frames_per_bucket) as usize;
+        let Some((min, max)) = buckets.get_mut(bucket) else { break; };
+        *min = min.min(sample);
+        *max = max.max(sample);
+    }
+    let quantize = |value: f32| (value.clamp(-1.0, 1.0) * 127.0).round() as i8;
+    let peaks = Peaks { buckets: buckets.into_iter().map(|(min, max)| (quantize(min), quantize(max))).collect() };
+    save_cached(key, &peaks);
+    Ok(peaks)
+}
+
+// --- Cache em disco ---
+// FNV-1a (estável entre versões, ao contrário do DefaultHasher) do caminho canônico, tamanho e
+// data de modificação, mais o trecho do CUE. Ler o arquivo inteiro só para o hash custaria quase
+// tanto quanto decodificá-lo; um arquivo editado ganha outra data e, com ela, outra chave.
+fn cache_key(path: &Path, start: Duration, end: Option<Duration>) -> io::Result<u64> {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    let canonical = fs::canonicalize(path)?;
+    let metadata = fs::metadata(&canonical)?;
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH).unwrap_or_default();
+    feed(canonical.to_string_lossy().as_bytes());
+    feed(&metadata.len().to_le_bytes());
+    feed(&modified.as_nanos().to_le_bytes());
+    feed(&start.as_millis().to_le_bytes());
+    feed(&end.map_or(0, |end| end.as_millis()).to_le_bytes());
+    Ok(hash)
+}
+
+fn cache_path(key: u64) -> Option<PathBuf> {
+    eframe::storage_dir(APP_NAME).map(|dir| dir.join(CACHE_DIR).join(format!("{:016x}.peaks", key)))
+}
+
+fn load_cached(key: u64) -> Option<Peaks> {
+    let bytes = fs::read(cache_path(key)?).ok()?;
+    Peaks::from_bytes(&bytes)
+}
+
+fn save_cached(key: u64, peaks: &Peaks) {
+    let Some(path) = cache_path(key) else { return; };
+    let result = path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::write(&path, peaks.to_bytes()));
+    if let Err(e) = result { eprintln!("Waveform: Failed to write cache {:?}: {}", path, e); }
+}
+
+// --- Cálculo em segundo plano ---
+// Uma faixa por vez: pedir outra cancela a anterior
+#[derive(Default)]
+pub struct WaveformLoader {
+    path: Option<PathBuf>,
+    peaks: Option<Arc<Peaks>>,
+    cancel: Arc<AtomicBool>,
+    receiver: Option<CrossbeamReceiver<(PathBuf, Result<Peaks, String>)>>,
+}
+
+impl WaveformLoader {
+    pub fn request(&mut self, path: Option<&Path>) {
+        if self.path.as_deref() == path { return; }
+        self.cancel.store(true, Ordering::Relaxed);
+        self.path = path.map(Path::to_path_buf);
+        self.peaks = None;
+        self.receiver = None;
+        let Some(path) = path.map(Path::to_path_buf) else { return; };
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel = Arc::clone(&cancel);
+        let (tx, rx): (CrossbeamSender<_>, CrossbeamReceiver<_>) = unbounded();
+        self.receiver = Some(rx);
+        thread::spawn(move || {
+            let result = compute(&path, &cancel);
+            let _ = tx.send((path, result));
+        });
+    }
+
+    // Recolhe o resultado quando fica pronto
+    pub fn poll(&mut self) -> Option<Arc<Peaks>> {
+        if let Some(receiver) = &self.receiver {
+            if let Ok((path, result)) = receiver.try_recv() {
+                self.receiver = None;
+                match result {
+                    Ok(peaks) if self.path.as_ref() == Some(&path) => self.peaks = Some(Arc::new(peaks)),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Waveform: {:?}: {}", path, e),
+                }
+            }
+        }
+        self.peaks.clone()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.receiver.is_some()
+    }
+}